@@ -14,6 +14,12 @@ pub struct BuildArgs {
     /// Compress built files to one tar.gz
     #[clap(short = 'a', long)]
     pub archive: bool,
+    /// Include draft posts and pages
+    #[clap(short = 'd', long)]
+    pub drafts: bool,
+    /// Include posts and pages dated in the future
+    #[clap(short = 'f', long)]
+    pub future: bool,
 }
 
 #[derive(Debug, Args)]
@@ -24,6 +30,12 @@ pub struct ServerArgs {
     /// Clean old files before current build
     #[clap(short = 'c', long)]
     pub clean: bool,
+    /// Include draft posts and pages
+    #[clap(short = 'd', long)]
+    pub drafts: bool,
+    /// Include posts and pages dated in the future
+    #[clap(short = 'f', long)]
+    pub future: bool,
 }
 
 #[derive(Debug, Args)]
@@ -32,4 +44,7 @@ pub struct CreateArgs {
     pub path: String,
     #[clap(short = 'p', long)]
     pub page: bool,
+    /// Front matter format, yaml, toml or json
+    #[clap(short = 'f', long, default_value = "yaml", value_parser = ["yaml", "toml", "json"])]
+    pub format: String,
 }
@@ -16,7 +16,11 @@ pub fn run_build_site(
     info!("Building start");
 
     let config_file = "config.toml";
-    let site = match models::Site::load(config_file) {
+    let options = models::SiteOptions {
+        drafts: args.drafts,
+        future: args.future,
+    };
+    let site = match models::Site::load(config_file, options) {
         Ok(site) => site,
         Err(err) => {
             error!("Load site failed: {}", err);
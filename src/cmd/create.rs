@@ -10,6 +10,15 @@ pub fn run_create(args: cmd::CreateArgs) {
     let config = models::Config::from_file(path).unwrap();
     debug!("Loaded config: {}", path);
 
+    let format = models::FrontMatterFormat::parse(&args.format).unwrap();
+    // date of new post is now in site timezone
+    let date = config
+        .get_timezone()
+        .unwrap()
+        .now()
+        .format("%Y-%m-%d %H:%M:%S")
+        .to_string();
+
     // 2. create output file
     let mut content_dir = config.get_posts_dir();
     if args.page {
@@ -22,34 +31,46 @@ pub fn run_create(args: cmd::CreateArgs) {
     // get basename as post title
     let basename = path.file_stem().unwrap().to_str().unwrap();
     if args.page {
-        create_empty_page(path.to_str().unwrap(), basename, &slug);
+        create_empty_page(path.to_str().unwrap(), basename, &slug, &date, format);
         info!("Created page: {}", path.to_str().unwrap());
         return;
     }
-    create_empty_post(path.to_str().unwrap(), basename, &slug);
+    create_empty_post(path.to_str().unwrap(), basename, &slug, &date, format);
     info!("Created post: {}", path.to_str().unwrap());
 }
 
-fn create_empty_post(path: &str, title: &str, slug: &str) {
+fn create_empty_post(
+    path: &str,
+    title: &str,
+    slug: &str,
+    date: &str,
+    format: models::FrontMatterFormat,
+) {
     let bytes = include_bytes!("initdata/new_post.md");
     let mut post = models::Post::default();
     post.meta.title = title.to_string();
     post.meta.slug = slug.to_string();
-    post.meta.date = chrono::Local::now().format("%Y-%m-%d %H:%M:%S").to_string();
+    post.meta.date = date.to_string();
     post.meta.tags = Some(vec![]);
     post.content_markdown
         .push_str(&String::from_utf8_lossy(bytes));
-    post.to_file(path).unwrap();
+    post.to_file(path, format).unwrap();
 }
 
-fn create_empty_page(path: &str, title: &str, slug: &str) {
+fn create_empty_page(
+    path: &str,
+    title: &str,
+    slug: &str,
+    date: &str,
+    format: models::FrontMatterFormat,
+) {
     let bytes = include_bytes!("initdata/new_post.md");
     let mut page = models::Post::default();
     page.meta.title = title.to_string();
     page.meta.slug = slug.to_string();
-    page.meta.date = chrono::Local::now().format("%Y-%m-%d %H:%M:%S").to_string();
+    page.meta.date = date.to_string();
     page.meta.template = Some("page.hbs".to_string());
     page.content_markdown
         .push_str(&String::from_utf8_lossy(bytes));
-    page.to_file(path).unwrap();
+    page.to_file(path, format).unwrap();
 }
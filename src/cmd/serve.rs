@@ -9,6 +9,8 @@ pub fn run_serve(args: cmd::ServerArgs) {
         watch_in_spawn: true,
         archive: false,
         clean: args.clean,
+        drafts: args.drafts,
+        future: args.future,
     };
     // build first, then start server
     let site = cmd::run_build_site(build_args).unwrap();
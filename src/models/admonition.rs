@@ -0,0 +1,179 @@
+use crate::models;
+use pulldown_cmark as cmark;
+
+// kinds of github style alerts, like > [!NOTE]
+const ALERT_KINDS: [&str; 5] = ["note", "tip", "important", "warning", "caution"];
+
+pub fn build_admonition_start(kind: &str, title: Option<&str>) -> String {
+    let kind: String = kind
+        .chars()
+        .filter(|c| c.is_alphanumeric() || *c == '-' || *c == '_')
+        .collect::<String>()
+        .to_lowercase();
+    let default_title = {
+        let mut chars = kind.chars();
+        match chars.next() {
+            Some(first) => first.to_uppercase().chain(chars).collect(),
+            None => String::new(),
+        }
+    };
+    let mut html = format!("<aside class=\"admonition {}\">", kind);
+    html.push_str("<p class=\"admonition-title\">");
+    cmark::escape::escape_html(&mut html, title.unwrap_or(&default_title)).unwrap();
+    html.push_str("</p>\n");
+    html
+}
+
+pub const ADMONITION_END: &str = "</aside>\n";
+
+// parse [!KIND] optional title at first line of blockquote
+fn parse_alert_marker(text: &str) -> Option<(String, Option<String>)> {
+    let rest = text.trim_start().strip_prefix("[!")?;
+    let (kind, title) = rest.split_once(']')?;
+    let kind = kind.to_lowercase();
+    if !ALERT_KINDS.contains(&kind.as_str()) {
+        return None;
+    }
+    let title = title.trim();
+    Some((kind, (!title.is_empty()).then(|| title.to_string())))
+}
+
+// convert events of one blockquote to admonition if it begins with alert marker
+fn convert_alert(events: Vec<cmark::Event>) -> Vec<cmark::Event> {
+    if !matches!(
+        events.get(1),
+        Some(cmark::Event::Start(cmark::Tag::Paragraph))
+    ) {
+        return events;
+    }
+    let mut text = String::new();
+    let mut pos = 2;
+    while let Some(cmark::Event::Text(t)) = events.get(pos) {
+        text.push_str(t);
+        pos += 1;
+    }
+    let (kind, title) = match parse_alert_marker(&text) {
+        Some(marker) => marker,
+        None => return events,
+    };
+    let mut converted = vec![cmark::Event::Html(
+        build_admonition_start(&kind, title.as_deref()).into(),
+    )];
+    match events.get(pos) {
+        // marker is the whole paragraph
+        Some(cmark::Event::End(cmark::Tag::Paragraph)) => pos += 1,
+        Some(cmark::Event::SoftBreak) | Some(cmark::Event::HardBreak) => {
+            converted.push(cmark::Event::Start(cmark::Tag::Paragraph));
+            pos += 1;
+        }
+        _ => return events,
+    }
+    let len = events.len();
+    converted.extend(events.into_iter().take(len - 1).skip(pos));
+    converted.push(cmark::Event::Html(ADMONITION_END.into()));
+    converted
+}
+
+// convert github style alerts in blockquotes, nested blockquotes are converted too
+pub fn convert_alerts(events: Vec<cmark::Event>) -> Vec<cmark::Event> {
+    let mut stack: Vec<Vec<cmark::Event>> = vec![vec![]];
+    for event in events {
+        match event {
+            cmark::Event::Start(cmark::Tag::BlockQuote) => stack.push(vec![event]),
+            cmark::Event::End(cmark::Tag::BlockQuote) => {
+                let mut inner = stack.pop().unwrap();
+                inner.push(event);
+                stack.last_mut().unwrap().extend(convert_alert(inner));
+            }
+            _ => stack.last_mut().unwrap().push(event),
+        }
+    }
+    stack.pop().unwrap()
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Container {
+    pub kind: String,
+    pub title: Option<String>,
+    pub range: std::ops::Range<usize>,
+    pub body: std::ops::Range<usize>,
+}
+
+// parse top level :::kind title ... ::: containers outside of code
+pub fn parse_containers(content: &str) -> Vec<Container> {
+    let code_ranges = models::markdown::parse_code_ranges(content);
+    let mut containers = vec![];
+    let mut stack: Vec<(usize, usize, String)> = vec![];
+    let mut offset = 0;
+    for line in content.split_inclusive('\n') {
+        let start = offset;
+        offset += line.len();
+        if code_ranges.iter().any(|r| r.contains(&start)) {
+            continue;
+        }
+        let marker = match line.trim().strip_prefix(":::") {
+            Some(marker) => marker.trim(),
+            None => continue,
+        };
+        if !marker.is_empty() {
+            stack.push((start, offset, marker.to_string()));
+            continue;
+        }
+        let (open_start, body_start, marker) = match stack.pop() {
+            Some(open) => open,
+            None => continue,
+        };
+        if stack.is_empty() {
+            let (kind, title) = marker
+                .split_once(char::is_whitespace)
+                .unwrap_or((&marker, ""));
+            let title = title.trim();
+            containers.push(Container {
+                kind: kind.to_string(),
+                title: (!title.is_empty()).then(|| title.to_string()),
+                range: open_start..offset,
+                body: body_start..start,
+            });
+        }
+    }
+    containers
+}
+
+#[cfg(test)]
+mod admonition_tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_alert_marker() {
+        assert_eq!(
+            parse_alert_marker("[!NOTE]"),
+            Some(("note".to_string(), None))
+        );
+        assert_eq!(
+            parse_alert_marker("[!Warning] Be careful"),
+            Some(("warning".to_string(), Some("Be careful".to_string())))
+        );
+        assert_eq!(parse_alert_marker("[!UNKNOWN]"), None);
+        assert_eq!(parse_alert_marker("normal quote"), None);
+        assert_eq!(
+            build_admonition_start("tip", None),
+            "<aside class=\"admonition tip\"><p class=\"admonition-title\">Tip</p>\n"
+        );
+    }
+
+    #[test]
+    fn test_parse_containers() {
+        let content = ":::tip Read this\nouter\n:::note\ninner\n:::\n:::\n\n```\n:::code\n```\n";
+        let containers = parse_containers(content);
+        assert_eq!(containers.len(), 1);
+        assert_eq!(containers[0].kind, "tip");
+        assert_eq!(containers[0].title, Some("Read this".to_string()));
+        assert_eq!(
+            &content[containers[0].body.clone()],
+            "outer\n:::note\ninner\n:::\n"
+        );
+        assert_eq!(&content[containers[0].range.clone()], &content[..45]);
+        // unclosed container is kept as text
+        assert!(parse_containers(":::tip\ntext").is_empty());
+    }
+}
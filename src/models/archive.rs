@@ -37,7 +37,12 @@ mod archive_tests {
                     comments: Some(true),
                     ..models::PostMetadata::default()
                 },
-                datetime: Some(chrono::NaiveDate::from_ymd(2021, 1, 1).and_hms(0, 0, 0)),
+                datetime: Some(
+                    chrono::NaiveDate::from_ymd_opt(2021, 1, 1)
+                        .unwrap()
+                        .and_hms_opt(0, 0, 0)
+                        .unwrap(),
+                ),
                 ..Default::default()
             },
             models::Post {
@@ -46,7 +51,12 @@ mod archive_tests {
                     comments: Some(true),
                     ..models::PostMetadata::default()
                 },
-                datetime: Some(chrono::NaiveDate::from_ymd(2022, 1, 2).and_hms(0, 0, 0)),
+                datetime: Some(
+                    chrono::NaiveDate::from_ymd_opt(2022, 1, 2)
+                        .unwrap()
+                        .and_hms_opt(0, 0, 0)
+                        .unwrap(),
+                ),
                 ..Default::default()
             },
             models::Post {
@@ -55,7 +65,12 @@ mod archive_tests {
                     comments: Some(true),
                     ..models::PostMetadata::default()
                 },
-                datetime: Some(chrono::NaiveDate::from_ymd(2023, 1, 3).and_hms(0, 0, 0)),
+                datetime: Some(
+                    chrono::NaiveDate::from_ymd_opt(2023, 1, 3)
+                        .unwrap()
+                        .and_hms_opt(0, 0, 0)
+                        .unwrap(),
+                ),
                 ..Default::default()
             },
         ];
@@ -0,0 +1,141 @@
+use crate::models;
+
+pub const DEFAULT_CATEGORY: &str = "uncategorized";
+
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
+pub struct Category {
+    pub name: String,
+    pub path: String,
+    pub parent: Option<String>,
+    pub depth: usize,
+    pub url: String,
+    pub page_format: String,
+    pub posts_index: Vec<usize>,
+}
+
+// each level of category path is slugified, like Web Dev/编程 to web-dev/bian-cheng
+pub fn build_category_slug(path: &str, slug_config: &models::SlugConfig) -> String {
+    path.split('/')
+        .map(|name| match models::slug::slugify(name, slug_config) {
+            slug if slug.is_empty() => name.to_string(),
+            slug => slug,
+        })
+        .collect::<Vec<String>>()
+        .join("/")
+}
+
+fn build_category_link(slug: &str, format: &str) -> String {
+    let link = format.to_string();
+    link.replace(":category", slug)
+}
+
+// categories in front matter are one hierarchy, ["a", "b"] means a > b
+pub fn build_category_path(categories: &[String]) -> String {
+    categories
+        .iter()
+        .map(|c| c.trim().trim_matches('/'))
+        .filter(|c| !c.is_empty())
+        .collect::<Vec<&str>>()
+        .join("/")
+}
+
+impl Category {
+    pub fn parse(
+        posts: &[models::Post],
+        url_config: &models::UrlConfig,
+        slug_config: &models::SlugConfig,
+    ) -> Vec<Category> {
+        let mut categories = std::collections::HashMap::new();
+        for (index, p) in posts.iter().enumerate() {
+            let names = match p.meta.categories.as_ref() {
+                Some(names) => names,
+                None => continue,
+            };
+            // post belongs to each level of its category path
+            let mut parent: Option<String> = None;
+            for depth in 1..=names.len() {
+                let path = build_category_path(&names[..depth]);
+                if path.is_empty() || parent.as_ref() == Some(&path) {
+                    continue;
+                }
+                let slug = build_category_slug(&path, slug_config);
+                let category = categories.entry(path.clone()).or_insert(Category {
+                    name: path.rsplit('/').next().unwrap().to_string(),
+                    path: path.clone(),
+                    parent: parent.clone(),
+                    depth: path.matches('/').count(),
+                    url: build_category_link(&slug, &url_config.category_link_format),
+                    page_format: build_category_link(&slug, &url_config.category_page_format),
+                    posts_index: vec![],
+                });
+                category.posts_index.push(index);
+                parent = Some(path);
+            }
+        }
+        let mut values: Vec<Category> = categories.into_values().collect();
+        values.sort_by(|a, b| a.path.cmp(&b.path));
+        values
+    }
+}
+
+#[cfg(test)]
+mod post_categories_test {
+    use super::*;
+
+    #[test]
+    fn test_categories() {
+        let posts = vec![
+            models::Post {
+                meta: models::PostMetadata {
+                    categories: Some(vec!["Programming".to_string(), "Rust".to_string()]),
+                    ..models::PostMetadata::default()
+                },
+                ..Default::default()
+            },
+            models::Post {
+                meta: models::PostMetadata {
+                    categories: Some(vec!["Programming".to_string(), "Go".to_string()]),
+                    ..models::PostMetadata::default()
+                },
+                ..Default::default()
+            },
+            models::Post {
+                meta: models::PostMetadata {
+                    categories: Some(vec!["Life".to_string()]),
+                    ..models::PostMetadata::default()
+                },
+                ..Default::default()
+            },
+            models::Post {
+                meta: models::PostMetadata {
+                    categories: Some(vec!["Web Dev".to_string(), "编程".to_string()]),
+                    ..models::PostMetadata::default()
+                },
+                ..Default::default()
+            },
+            models::Post::default(),
+        ];
+        let url_config = models::UrlConfig::new();
+        let categories = Category::parse(&posts, &url_config, &models::SlugConfig::default());
+        // Life, Programming, Programming/Go, Programming/Rust, Web Dev, Web Dev/编程
+        assert_eq!(categories.len(), 6);
+        assert_eq!(categories[0].path, "Life");
+        assert_eq!(categories[1].path, "Programming");
+        assert_eq!(categories[1].posts_index.len(), 2); // parent contains posts of children
+        assert_eq!(categories[1].parent, None);
+
+        assert_eq!(categories[3].name, "Rust");
+        assert_eq!(categories[3].path, "Programming/Rust");
+        assert_eq!(categories[3].parent, Some("Programming".to_string()));
+        assert_eq!(categories[3].depth, 1);
+        assert_eq!(categories[3].url, "/category/programming/rust");
+        assert_eq!(
+            categories[3].page_format,
+            "/category/programming/rust/page/:page"
+        );
+
+        // names with spaces and non-ascii chars are slugified in urls
+        assert_eq!(categories[5].path, "Web Dev/编程");
+        assert_eq!(categories[5].url, "/category/web-dev/bian-cheng");
+    }
+}
@@ -7,6 +7,13 @@ pub struct DirectoryConfig {
     pub output: String,
     pub themes: String,
     pub assets: Vec<String>,
+    // translation strings of site, override ones of theme
+    #[serde(default = "default_i18n_dir")]
+    pub i18n: String,
+}
+
+fn default_i18n_dir() -> String {
+    String::from("i18n")
 }
 
 impl DirectoryConfig {
@@ -16,6 +23,7 @@ impl DirectoryConfig {
             output: String::from("dist"),
             themes: String::from("themes"),
             assets: ["assets".to_string()].to_vec(),
+            i18n: default_i18n_dir(),
         }
     }
 }
@@ -29,6 +37,25 @@ pub struct UrlConfig {
     pub per_page_size: usize,
     pub tag_link_format: String,
     pub tag_page_format: String,
+    #[serde(default = "default_category_link_format")]
+    pub category_link_format: String,
+    #[serde(default = "default_category_page_format")]
+    pub category_page_format: String,
+    // tokens are same as post_link_format
+    #[serde(default = "default_page_link_format")]
+    pub page_link_format: String,
+}
+
+fn default_page_link_format() -> String {
+    String::from(":slug")
+}
+
+fn default_category_link_format() -> String {
+    String::from("/category/:category")
+}
+
+fn default_category_page_format() -> String {
+    String::from("/category/:category/page/:page")
 }
 
 impl UrlConfig {
@@ -40,6 +67,9 @@ impl UrlConfig {
             post_page_format: String::from("/page/:page"),
             tag_link_format: String::from("/tag/:tag"),
             tag_page_format: String::from("/tag/:tag/page/:page"),
+            category_link_format: default_category_link_format(),
+            category_page_format: default_category_page_format(),
+            page_link_format: default_page_link_format(),
             per_page_size: 10,
         }
     }
@@ -77,6 +107,31 @@ pub struct SiteConfig {
     pub keywords: Vec<String>,
     pub language: String,
     pub author: String,
+    #[serde(default = "default_related_posts")]
+    pub related_posts: usize,
+    // words per minute, and chinese or japanese chars per minute
+    #[serde(default = "default_reading_speed")]
+    pub reading_speed: usize,
+    #[serde(default = "default_cjk_reading_speed")]
+    pub cjk_reading_speed: usize,
+    // IANA name like Asia/Shanghai, or offset like +08:00, timezone of build machine if not set
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timezone: Option<String>,
+    // extra chrono formats of front matter times, like %d.%m.%Y %H:%M
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub date_formats: Vec<String>,
+}
+
+fn default_related_posts() -> usize {
+    5
+}
+
+fn default_reading_speed() -> usize {
+    200
+}
+
+fn default_cjk_reading_speed() -> usize {
+    400
 }
 
 impl SiteConfig {
@@ -95,10 +150,180 @@ impl SiteConfig {
             .to_vec(),
             language: "en".to_string(),
             author: "pugo".to_string(),
+            related_posts: default_related_posts(),
+            reading_speed: default_reading_speed(),
+            cjk_reading_speed: default_cjk_reading_speed(),
+            timezone: None,
+            date_formats: vec![],
+        }
+    }
+}
+
+// site config overridden for one language
+#[derive(Debug, Default, Clone, serde::Serialize, serde::Deserialize)]
+pub struct LanguageConfig {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub subtitle: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct HighlightConfig {
+    pub enabled: bool,
+    // "class" outputs css classes and exports css file, "inline" outputs inline styles
+    pub style: String,
+    pub theme: String,
+    pub css_file: String,
+}
+
+impl Default for HighlightConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            style: "class".to_string(),
+            theme: "InspiredGitHub".to_string(),
+            css_file: "static/css/highlight.css".to_string(),
+        }
+    }
+}
+
+impl HighlightConfig {
+    pub fn is_inline(&self) -> bool {
+        self.style == "inline"
+    }
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct ExcerptConfig {
+    // excerpt when no <!-- more --> marker, "paragraphs", "characters" or "full"
+    pub strategy: String,
+    pub paragraphs: usize,
+    pub characters: usize,
+}
+
+impl Default for ExcerptConfig {
+    fn default() -> Self {
+        Self {
+            strategy: "full".to_string(),
+            paragraphs: 2,
+            characters: 200,
         }
     }
 }
 
+// slug of post or page without slug in front matter
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct SlugConfig {
+    // "title", or "filename" that is name of file or bundle directory
+    pub source: String,
+    pub separator: String,
+    // "lower", "upper" or "keep"
+    pub case: String,
+    // convert unicode to ascii, like 你好 to ni-hao
+    pub transliterate: bool,
+}
+
+impl Default for SlugConfig {
+    fn default() -> Self {
+        Self {
+            source: "title".to_string(),
+            separator: "-".to_string(),
+            case: "lower".to_string(),
+            transliterate: true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct MarkdownConfig {
+    pub smart_punctuation: bool,
+    // {#id .class} after heading text
+    pub heading_attributes: bool,
+    pub definition_lists: bool,
+    // every newline in paragraph is a line break
+    pub hard_breaks: bool,
+    // keep raw html in markdown, it is stripped if false
+    pub unsafe_html: bool,
+    pub external_links_target_blank: bool,
+    pub external_links_no_follow: bool,
+    pub external_links_no_referrer: bool,
+}
+
+impl Default for MarkdownConfig {
+    fn default() -> Self {
+        Self {
+            smart_punctuation: false,
+            heading_attributes: false,
+            definition_lists: false,
+            hard_breaks: false,
+            unsafe_html: true,
+            external_links_target_blank: false,
+            external_links_no_follow: false,
+            external_links_no_referrer: false,
+        }
+    }
+}
+
+impl MarkdownConfig {
+    pub fn with_overrides(&self, overrides: &MarkdownOverrides) -> MarkdownConfig {
+        MarkdownConfig {
+            smart_punctuation: overrides
+                .smart_punctuation
+                .unwrap_or(self.smart_punctuation),
+            heading_attributes: overrides
+                .heading_attributes
+                .unwrap_or(self.heading_attributes),
+            definition_lists: overrides.definition_lists.unwrap_or(self.definition_lists),
+            hard_breaks: overrides.hard_breaks.unwrap_or(self.hard_breaks),
+            unsafe_html: overrides.unsafe_html.unwrap_or(self.unsafe_html),
+            external_links_target_blank: overrides
+                .external_links_target_blank
+                .unwrap_or(self.external_links_target_blank),
+            external_links_no_follow: overrides
+                .external_links_no_follow
+                .unwrap_or(self.external_links_no_follow),
+            external_links_no_referrer: overrides
+                .external_links_no_referrer
+                .unwrap_or(self.external_links_no_referrer),
+        }
+    }
+}
+
+// markdown options in front matter of post, unset ones use site config
+#[derive(Debug, Default, Clone, serde::Serialize, serde::Deserialize)]
+pub struct MarkdownOverrides {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub smart_punctuation: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub heading_attributes: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub definition_lists: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hard_breaks: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub unsafe_html: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub external_links_target_blank: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub external_links_no_follow: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub external_links_no_referrer: Option<bool>,
+}
+
+#[derive(Debug, Default, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct MathConfig {
+    // convert $...$, $$...$$ and ```math blocks to mathml
+    pub enabled: bool,
+}
+
 #[derive(Debug, serde::Serialize, serde::Deserialize)]
 pub struct Config {
     pub site: SiteConfig,
@@ -107,6 +332,20 @@ pub struct Config {
     pub theme: ThemeConfig,
     pub nav: Vec<NavConfig>,
     pub author: Option<std::collections::HashMap<String, models::Author>>,
+    #[serde(default, rename = "taxonomy", skip_serializing_if = "Vec::is_empty")]
+    pub taxonomies: Vec<models::TaxonomyConfig>,
+    #[serde(default)]
+    pub highlight: HighlightConfig,
+    #[serde(default)]
+    pub excerpt: ExcerptConfig,
+    #[serde(default)]
+    pub math: MathConfig,
+    #[serde(default)]
+    pub markdown: MarkdownConfig,
+    #[serde(default)]
+    pub slug: SlugConfig,
+    #[serde(default, skip_serializing_if = "std::collections::BTreeMap::is_empty")]
+    pub languages: std::collections::BTreeMap<String, LanguageConfig>,
 }
 
 impl Config {
@@ -129,6 +368,13 @@ impl Config {
                 },
             ],
             author: Some(std::collections::HashMap::new()),
+            taxonomies: vec![],
+            highlight: HighlightConfig::default(),
+            excerpt: ExcerptConfig::default(),
+            math: MathConfig::default(),
+            markdown: MarkdownConfig::default(),
+            slug: SlugConfig::default(),
+            languages: std::collections::BTreeMap::new(),
         };
         let author = models::Author::default();
         cfg.author
@@ -204,6 +450,13 @@ impl Config {
         Ok(())
     }
 
+    pub fn get_timezone(&self) -> Result<models::Timezone, Box<dyn std::error::Error>> {
+        match &self.site.timezone {
+            Some(name) => Ok(models::Timezone::parse(name)?),
+            None => Ok(models::Timezone::default()),
+        }
+    }
+
     pub fn get_author(&self, name: &str) -> models::Author {
         if self.author.is_none() {
             return models::Author::create_by_name(name);
@@ -301,4 +554,20 @@ mod tests {
         assert_eq!(config.get_author("abc").name, "abc");
         assert_eq!(config.get_default_author().name, "author");
     }
+
+    #[test]
+    fn test_empty_math_config() {
+        let math: MathConfig = toml::from_str("").unwrap();
+        assert!(!math.enabled);
+    }
+
+    #[test]
+    fn test_partial_highlight_config() {
+        // missing fields use defaults
+        let highlight: HighlightConfig = toml::from_str("theme = \"base16-ocean.dark\"").unwrap();
+        assert!(highlight.enabled);
+        assert_eq!(highlight.style, "class");
+        assert_eq!(highlight.theme, "base16-ocean.dark");
+        assert_eq!(highlight.css_file, "static/css/highlight.css");
+    }
 }
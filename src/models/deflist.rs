@@ -0,0 +1,110 @@
+use pulldown_cmark as cmark;
+
+const DEFLIST_START: &str = "<dl>\n";
+const DEFLIST_END: &str = "</dl>\n";
+
+// split inline events of paragraph into lines at soft or hard breaks
+fn split_lines(events: Vec<cmark::Event>) -> Vec<Vec<cmark::Event>> {
+    let mut lines = vec![vec![]];
+    for event in events {
+        match event {
+            cmark::Event::SoftBreak | cmark::Event::HardBreak => lines.push(vec![]),
+            _ => lines.last_mut().unwrap().push(event),
+        }
+    }
+    lines
+}
+
+// strip ": " marker of definition line
+fn strip_marker(line: &mut [cmark::Event]) -> bool {
+    let text = match line.first_mut() {
+        Some(cmark::Event::Text(text)) => text,
+        _ => return false,
+    };
+    let rest = match text.strip_prefix(": ") {
+        Some(rest) => rest.trim_start().to_string(),
+        None => return false,
+    };
+    *text = rest.into();
+    true
+}
+
+// convert paragraph like "term\n: definition" to definition list,
+// returns the events back if it is not a definition list
+fn convert_paragraph(events: Vec<cmark::Event>) -> Result<Vec<cmark::Event>, Vec<cmark::Event>> {
+    let mut lines = split_lines(events.clone());
+    let markers: Vec<bool> = lines.iter_mut().map(|l| strip_marker(l)).collect();
+    if markers[0] || !markers.iter().any(|m| *m) {
+        return Err(events);
+    }
+    let mut converted = vec![];
+    for (line, is_definition) in lines.into_iter().zip(markers) {
+        let (start, end) = if is_definition {
+            ("<dd>", "</dd>\n")
+        } else {
+            ("<dt>", "</dt>\n")
+        };
+        converted.push(cmark::Event::Html(start.into()));
+        converted.extend(line);
+        converted.push(cmark::Event::Html(end.into()));
+    }
+    Ok(converted)
+}
+
+// convert definition lists in paragraphs, adjacent lists are merged
+pub fn convert_definition_lists(events: Vec<cmark::Event>) -> Vec<cmark::Event> {
+    let mut output = vec![];
+    let mut paragraph: Option<Vec<cmark::Event>> = None;
+    for event in events {
+        match event {
+            cmark::Event::Start(cmark::Tag::Paragraph) => paragraph = Some(vec![]),
+            cmark::Event::End(cmark::Tag::Paragraph) => {
+                match convert_paragraph(paragraph.take().unwrap_or_default()) {
+                    Ok(items) => {
+                        if matches!(output.last(), Some(cmark::Event::Html(html)) if html.as_ref() == DEFLIST_END)
+                        {
+                            output.pop();
+                        } else {
+                            output.push(cmark::Event::Html(DEFLIST_START.into()));
+                        }
+                        output.extend(items);
+                        output.push(cmark::Event::Html(DEFLIST_END.into()));
+                    }
+                    Err(inner) => {
+                        output.push(cmark::Event::Start(cmark::Tag::Paragraph));
+                        output.extend(inner);
+                        output.push(event);
+                    }
+                }
+            }
+            _ => match paragraph.as_mut() {
+                Some(inner) => inner.push(event),
+                None => output.push(event),
+            },
+        }
+    }
+    output
+}
+
+#[cfg(test)]
+mod deflist_tests {
+    use super::*;
+
+    fn render(content: &str) -> String {
+        let events = cmark::Parser::new(content).collect();
+        let mut html = String::new();
+        cmark::html::push_html(&mut html, convert_definition_lists(events).into_iter());
+        html
+    }
+
+    #[test]
+    fn test_convert_definition_lists() {
+        assert_eq!(
+            render("Apple\n: A *red* fruit\n: A company\n\nOrange\n: Citrus\n"),
+            "<dl>\n<dt>Apple</dt>\n<dd>A <em>red</em> fruit</dd>\n<dd>A company</dd>\n<dt>Orange</dt>\n<dd>Citrus</dd>\n</dl>\n"
+        );
+        assert_eq!(render("plain\ntext\n"), "<p>plain\ntext</p>\n");
+        assert_eq!(render(": no term\n"), "<p>: no term</p>\n");
+        assert_eq!(render("key:value\n"), "<p>key:value</p>\n");
+    }
+}
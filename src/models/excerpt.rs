@@ -0,0 +1,277 @@
+use crate::models;
+use pulldown_cmark as cmark;
+
+// top level block, containers and paired shortcodes are one block
+struct Block {
+    range: std::ops::Range<usize>,
+    paragraph: bool,
+}
+
+// ranges of :::kind containers, shortcodes and math, they are never cut
+fn parse_unbreakable_ranges(content: &str) -> Vec<std::ops::Range<usize>> {
+    let mut ranges: Vec<std::ops::Range<usize>> = models::admonition::parse_containers(content)
+        .into_iter()
+        .map(|c| c.range)
+        .collect();
+    // invalid shortcodes are reported when rendering
+    let shortcodes = models::shortcode::parse_shortcodes(content).unwrap_or_default();
+    ranges.extend(shortcodes.into_iter().map(|s| s.range));
+    ranges.extend(
+        models::math::parse_math_spans(content)
+            .into_iter()
+            .map(|m| m.range),
+    );
+    ranges.sort_by_key(|r| r.start);
+    ranges
+}
+
+// source range of each top level block
+fn parse_blocks(content: &str) -> Vec<Block> {
+    let unbreakable = parse_unbreakable_ranges(content);
+    let mut blocks: Vec<Block> = vec![];
+    let mut depth = 0;
+    let parser = cmark::Parser::new_ext(content, cmark::Options::all());
+    for (event, range) in parser.into_offset_iter() {
+        let paragraph = matches!(event, cmark::Event::Start(cmark::Tag::Paragraph));
+        match event {
+            cmark::Event::Start(_) => {
+                depth += 1;
+                if depth > 1 {
+                    continue;
+                }
+            }
+            cmark::Event::End(_) => {
+                depth -= 1;
+                continue;
+            }
+            _ if depth > 0 => continue,
+            _ => {}
+        }
+        let mut block = Block { range, paragraph };
+        // blocks overlapped with unbreakable range across blocks are merged into one block,
+        // range inside one paragraph is kept by moving the cut before it
+        if let Some(r) = unbreakable.iter().find(|r| {
+            r.start < block.range.end
+                && block.range.start < r.end
+                && (r.start < block.range.start || r.end > block.range.end)
+        }) {
+            block.range = r.start.min(block.range.start)..r.end.max(block.range.end);
+            block.paragraph = false;
+        }
+        match blocks.last_mut() {
+            Some(last) if last.range.end > block.range.start => {
+                last.range.end = last.range.end.max(block.range.end);
+                last.paragraph = false;
+            }
+            _ => blocks.push(block),
+        }
+    }
+    blocks
+}
+
+fn excerpt_by_paragraphs(content: &str, count: usize) -> String {
+    let mut end = 0;
+    let mut paragraphs = 0;
+    for block in parse_blocks(content) {
+        if paragraphs >= count {
+            break;
+        }
+        if block.paragraph {
+            paragraphs += 1;
+        }
+        end = block.range.end;
+    }
+    content[..end].trim().to_string()
+}
+
+// find position to cut text, prefer sentence end, then word end
+fn find_text_cut(text: &str, max_chars: usize) -> usize {
+    let allowed = text
+        .char_indices()
+        .nth(max_chars)
+        .map_or(text.len(), |(i, _)| i);
+    let head = &text[..allowed];
+    if let Some((i, c)) = head
+        .char_indices()
+        .rev()
+        .find(|(_, c)| matches!(c, '.' | '!' | '?' | '。' | '！' | '？'))
+    {
+        return i + c.len_utf8();
+    }
+    if allowed < text.len() && !text[allowed..].starts_with(char::is_whitespace) {
+        if let Some(i) = head.rfind(char::is_whitespace) {
+            return i;
+        }
+    }
+    allowed
+}
+
+// cut inside paragraph only at plain text of top level, so inline markup is kept complete
+fn cut_paragraph(content: &str, range: std::ops::Range<usize>, max_chars: usize) -> usize {
+    let paragraph = &content[range.clone()];
+    let mut remain = max_chars;
+    let mut inline_start: Option<usize> = None;
+    let mut depth = 0;
+    let parser = cmark::Parser::new_ext(paragraph, cmark::Options::all());
+    for (event, r) in parser.into_offset_iter() {
+        match event {
+            cmark::Event::Start(cmark::Tag::Paragraph)
+            | cmark::Event::End(cmark::Tag::Paragraph) => {}
+            cmark::Event::Start(_) => {
+                if depth == 0 {
+                    inline_start = Some(r.start);
+                }
+                depth += 1;
+            }
+            cmark::Event::End(_) => depth -= 1,
+            cmark::Event::Text(t) if depth == 0 => {
+                let chars = t.chars().count();
+                if chars > remain {
+                    let source = &paragraph[r.clone()];
+                    return range.start + r.start + find_text_cut(source, remain);
+                }
+                remain -= chars;
+            }
+            _ => {
+                let chars = match &event {
+                    cmark::Event::Text(t) | cmark::Event::Code(t) => t.chars().count(),
+                    _ => 0,
+                };
+                if chars > remain {
+                    return range.start
+                        + if depth == 0 {
+                            r.start
+                        } else {
+                            inline_start.unwrap()
+                        };
+                }
+                remain -= chars;
+            }
+        }
+    }
+    range.end
+}
+
+fn excerpt_by_characters(content: &str, count: usize) -> String {
+    let unbreakable = parse_unbreakable_ranges(content);
+    let mut end = 0;
+    let mut remain = count;
+    for Block { range, paragraph } in parse_blocks(content) {
+        let chars = models::markdown_to_text(&content[range.clone()])
+            .trim()
+            .chars()
+            .count();
+        if chars <= remain {
+            remain -= chars;
+            end = range.end;
+            continue;
+        }
+        // only paragraph is cut, other blocks are kept or dropped as a whole
+        if paragraph && remain > 0 {
+            let mut cut = cut_paragraph(content, range.clone(), remain);
+            if let Some(r) = unbreakable.iter().find(|r| r.start < cut && cut < r.end) {
+                cut = r.start;
+            }
+            if cut > end && !content[end..cut].trim().is_empty() {
+                return format!("{}…", content[..cut].trim_end());
+            }
+        }
+        if end == 0 {
+            end = range.end;
+        }
+        break;
+    }
+    content[..end].trim().to_string()
+}
+
+pub fn build_excerpt(content: &str, config: &models::ExcerptConfig) -> String {
+    match config.strategy.as_str() {
+        "paragraphs" => excerpt_by_paragraphs(content, config.paragraphs),
+        "characters" => excerpt_by_characters(content, config.characters),
+        _ => content.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod excerpt_tests {
+    use super::*;
+
+    const CONTENT: &str = "# Title\n\nFirst paragraph. It has **bold words** inside.\n\n```rust\nfn main() {}\n```\n\nSecond paragraph.\n\nThird paragraph.";
+
+    #[test]
+    fn test_excerpt_by_paragraphs() {
+        assert_eq!(
+            excerpt_by_paragraphs(CONTENT, 1),
+            "# Title\n\nFirst paragraph. It has **bold words** inside."
+        );
+        let excerpt = excerpt_by_paragraphs(CONTENT, 2);
+        assert!(excerpt.ends_with("```\n\nSecond paragraph."));
+        assert_eq!(excerpt_by_paragraphs(CONTENT, 10), CONTENT);
+    }
+
+    #[test]
+    fn test_excerpt_by_characters() {
+        // cut before inline markup
+        assert_eq!(
+            excerpt_by_characters(CONTENT, 30),
+            "# Title\n\nFirst paragraph. It has…"
+        );
+        // cut at sentence end
+        assert_eq!(
+            excerpt_by_characters("One sentence. Two sentences here", 20),
+            "One sentence.…"
+        );
+        // never cut inside bold text
+        assert_eq!(
+            excerpt_by_characters("Some words and **bold text here** end", 20),
+            "Some words and…"
+        );
+        // cut at word end
+        assert_eq!(excerpt_by_characters("one two three four", 10), "one two…");
+        assert_eq!(
+            excerpt_by_characters("中文内容不使用空格分词", 4),
+            "中文内容…"
+        );
+        assert_eq!(excerpt_by_characters("short", 100), "short");
+    }
+
+    #[test]
+    fn test_excerpt_unbreakable_blocks() {
+        let content = "First.\n\n:::tip\nTip one.\n\nTip two.\n:::\n\nSecond.\n\nThird.";
+        assert_eq!(
+            excerpt_by_paragraphs(content, 2),
+            "First.\n\n:::tip\nTip one.\n\nTip two.\n:::\n\nSecond."
+        );
+        assert_eq!(excerpt_by_characters(content, 10), "First.");
+
+        let content = "First.\n\n{{< note >}}\nNote one.\n\nNote two.\n{{< /note >}}\n\nSecond.";
+        assert_eq!(
+            excerpt_by_paragraphs(content, 2),
+            "First.\n\n{{< note >}}\nNote one.\n\nNote two.\n{{< /note >}}\n\nSecond."
+        );
+        assert_eq!(excerpt_by_characters(content, 15), "First.");
+    }
+
+    #[test]
+    fn test_excerpt_inline_unbreakable() {
+        // cut moves before single shortcode
+        assert_eq!(
+            excerpt_by_characters("Some words {{< icon star >}} more text after it", 15),
+            "Some words…"
+        );
+        // cut moves before inline math
+        assert_eq!(
+            excerpt_by_characters("Area $x^2 + y^2$ of circle", 8),
+            "Area…"
+        );
+    }
+
+    #[test]
+    fn test_build_excerpt() {
+        let config = models::ExcerptConfig {
+            strategy: "full".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(build_excerpt(CONTENT, &config), CONTENT);
+    }
+}
@@ -0,0 +1,165 @@
+use crate::models;
+use syntect::highlighting::ThemeSet;
+use syntect::html::{ClassStyle, ClassedHTMLGenerator, IncludeBackground};
+use syntect::parsing::SyntaxSet;
+use syntect::util::LinesWithEndings;
+
+const CLASS_STYLE: ClassStyle = ClassStyle::SpacedPrefixed { prefix: "hl-" };
+
+pub struct Highlighter {
+    syntax_set: SyntaxSet,
+    theme: syntect::highlighting::Theme,
+    inline: bool,
+}
+
+impl Highlighter {
+    pub fn new(
+        config: &models::HighlightConfig,
+    ) -> Result<Highlighter, Box<dyn std::error::Error>> {
+        // theme is one of syntect default themes, or path to a .tmTheme file
+        let theme = if config.theme.ends_with(".tmTheme") {
+            ThemeSet::get_theme(&config.theme)?
+        } else {
+            let mut themes = ThemeSet::load_defaults();
+            match themes.themes.remove(&config.theme) {
+                Some(theme) => theme,
+                None => return Err(format!("highlight theme not found: {}", config.theme).into()),
+            }
+        };
+        Ok(Highlighter {
+            syntax_set: SyntaxSet::load_defaults_newlines(),
+            theme,
+            inline: config.is_inline(),
+        })
+    }
+
+    pub fn css(&self) -> Result<String, Box<dyn std::error::Error>> {
+        Ok(syntect::html::css_for_theme_with_class_style(
+            &self.theme,
+            CLASS_STYLE,
+        )?)
+    }
+
+    // class of <pre>, and its inline style if needed
+    pub fn pre_attributes(&self) -> String {
+        if !self.inline {
+            return "class=\"highlight hl-code\"".to_string();
+        }
+        let mut attrs = "class=\"highlight\"".to_string();
+        if let Some(bg) = self.theme.settings.background {
+            attrs.push_str(&format!(
+                " style=\"background-color:#{:02x}{:02x}{:02x};\"",
+                bg.r, bg.g, bg.b
+            ));
+        }
+        attrs
+    }
+
+    // highlight code to html lines, each line is closed html without the newline
+    pub fn highlight_lines(&self, code: &str, lang: &str) -> Option<Vec<String>> {
+        let syntax = self.syntax_set.find_syntax_by_token(lang)?;
+        if self.inline {
+            let mut h = syntect::easy::HighlightLines::new(syntax, &self.theme);
+            let mut lines = vec![];
+            for line in LinesWithEndings::from(code) {
+                let regions = h.highlight_line(line, &self.syntax_set).ok()?;
+                let html =
+                    syntect::html::styled_line_to_highlighted_html(&regions, IncludeBackground::No)
+                        .ok()?;
+                lines.push(html.trim_end_matches('\n').to_string());
+            }
+            return Some(lines);
+        }
+        let mut generator =
+            ClassedHTMLGenerator::new_with_class_style(syntax, &self.syntax_set, CLASS_STYLE);
+        for line in LinesWithEndings::from(code) {
+            generator
+                .parse_html_for_line_which_includes_newline(line)
+                .ok()?;
+        }
+        Some(split_html_lines(&generator.finalize()))
+    }
+}
+
+// split highlighted html to lines, spans across lines are closed and reopened
+pub fn split_html_lines(html: &str) -> Vec<String> {
+    let mut lines = vec![];
+    let mut open_tags: Vec<&str> = vec![];
+    let mut current = String::new();
+    let mut has_text = false;
+    let mut rest = html;
+    while !rest.is_empty() {
+        if rest.starts_with("<span") {
+            let end = rest.find('>').map_or(rest.len(), |i| i + 1);
+            open_tags.push(&rest[..end]);
+            current.push_str(&rest[..end]);
+            rest = &rest[end..];
+        } else if rest.starts_with("</span>") {
+            open_tags.pop();
+            current.push_str("</span>");
+            rest = &rest["</span>".len()..];
+        } else if rest.starts_with('\n') {
+            current.push_str(&"</span>".repeat(open_tags.len()));
+            lines.push(current);
+            current = open_tags.concat();
+            has_text = false;
+            rest = &rest[1..];
+        } else {
+            let end = rest
+                .char_indices()
+                .skip(1)
+                .find(|(_, c)| *c == '<' || *c == '\n')
+                .map_or(rest.len(), |(i, _)| i);
+            current.push_str(&rest[..end]);
+            has_text = true;
+            rest = &rest[end..];
+        }
+    }
+    // last line without newline
+    if has_text {
+        current.push_str(&"</span>".repeat(open_tags.len()));
+        lines.push(current);
+    }
+    lines
+}
+
+#[cfg(test)]
+mod highlight_tests {
+    use super::*;
+
+    #[test]
+    fn test_split_html_lines() {
+        let html = "<span class=\"a\">fn main() {\n    <span class=\"b\">1</span>\n}</span>\n";
+        let lines = split_html_lines(html);
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[0], "<span class=\"a\">fn main() {</span>");
+        assert_eq!(
+            lines[1],
+            "<span class=\"a\">    <span class=\"b\">1</span></span>"
+        );
+        assert_eq!(lines[2], "<span class=\"a\">}</span>");
+    }
+
+    #[test]
+    fn test_highlight_lines() {
+        let mut config = models::HighlightConfig::default();
+        let highlighter = Highlighter::new(&config).unwrap();
+        let lines = highlighter
+            .highlight_lines("fn main() {\n    let a = \"中文\";\n}\n", "rust")
+            .unwrap();
+        assert_eq!(lines.len(), 3);
+        assert!(lines[0].contains("hl-storage hl-type hl-function"));
+        assert!(lines[1].contains("中文"));
+        assert!(highlighter.highlight_lines("abc", "no-such-lang").is_none());
+        assert!(highlighter.css().unwrap().contains(".hl-code"));
+
+        config.style = "inline".to_string();
+        let highlighter = Highlighter::new(&config).unwrap();
+        let lines = highlighter.highlight_lines("let a = 1;\n", "rs").unwrap();
+        assert!(lines[0].contains("style=\"color:"));
+        assert!(highlighter.pre_attributes().contains("background-color"));
+
+        config.theme = "no-such-theme".to_string();
+        assert!(Highlighter::new(&config).is_err());
+    }
+}
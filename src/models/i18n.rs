@@ -0,0 +1,155 @@
+use log::debug;
+
+// message of key, plural message is chosen by count, like { one = "...", other = "..." }
+#[derive(Debug, Clone, serde::Deserialize)]
+#[serde(untagged)]
+enum Message {
+    Text(String),
+    Plural(std::collections::HashMap<String, String>),
+}
+
+impl Message {
+    fn select(&self, count: Option<i64>) -> Option<&str> {
+        let forms = match self {
+            Message::Text(text) => return Some(text),
+            Message::Plural(forms) => forms,
+        };
+        let form = match count {
+            Some(0) if forms.contains_key("zero") => "zero",
+            Some(1) | Some(-1) if forms.contains_key("one") => "one",
+            _ => "other",
+        };
+        forms.get(form).map(|s| s.as_str())
+    }
+}
+
+// translation strings of each language, loaded from i18n/<lang>.toml
+#[derive(Debug, Default, Clone)]
+pub struct I18n {
+    default_language: String,
+    tables: std::collections::HashMap<String, std::collections::HashMap<String, Message>>,
+}
+
+impl I18n {
+    pub fn new(default_language: &str) -> I18n {
+        I18n {
+            default_language: default_language.to_string(),
+            tables: std::collections::HashMap::new(),
+        }
+    }
+
+    // load <lang>.toml files in dir, keys in later loaded dirs override former ones
+    pub fn load_dir(&mut self, dir: &str) -> Result<(), Box<dyn std::error::Error>> {
+        let entries = match std::fs::read_dir(dir) {
+            Ok(entries) => entries,
+            Err(_) => return Ok(()),
+        };
+        for entry in entries {
+            let path = entry?.path();
+            if path.extension().is_none_or(|ext| ext != "toml") {
+                continue;
+            }
+            let language = path.file_stem().unwrap().to_string_lossy().to_string();
+            let content = std::fs::read_to_string(&path)?;
+            let table: std::collections::HashMap<String, Message> = toml::from_str(&content)
+                .map_err(|e| format!("i18n file is not valid: {}, {}", path.display(), e))?;
+            self.tables.entry(language).or_default().extend(table);
+            debug!("Loaded i18n: {}", path.display());
+        }
+        Ok(())
+    }
+
+    // translate key in language, fall back to default language
+    pub fn translate(&self, language: &str, key: &str, count: Option<i64>) -> Option<&str> {
+        [language, &self.default_language]
+            .iter()
+            .filter_map(|l| self.tables.get(*l)?.get(key))
+            .find_map(|m| m.select(count))
+    }
+}
+
+// {{t "key" count=n name=value}}, {name} in message is replaced by value,
+// language is lang argument, or language of current page
+impl handlebars::HelperDef for I18n {
+    fn call_inner<'reg: 'rc, 'rc>(
+        &self,
+        h: &handlebars::Helper<'reg, 'rc>,
+        _: &'reg handlebars::Handlebars<'reg>,
+        ctx: &'rc handlebars::Context,
+        _: &mut handlebars::RenderContext<'reg, 'rc>,
+    ) -> Result<handlebars::ScopedJson<'reg, 'rc>, handlebars::RenderError> {
+        let key = h
+            .param(0)
+            .and_then(|v| v.value().as_str())
+            .ok_or_else(|| handlebars::RenderError::new("t helper needs a key"))?;
+        let language = h
+            .hash_get("lang")
+            .and_then(|v| v.value().as_str())
+            .or_else(|| ctx.data().pointer("/site/language")?.as_str())
+            .unwrap_or(&self.default_language);
+        let count = h.hash_get("count").and_then(|v| v.value().as_i64());
+        let mut text = self
+            .translate(language, key, count)
+            .unwrap_or(key)
+            .to_string();
+        for (name, value) in h.hash() {
+            let value = match value.value() {
+                serde_json::Value::String(s) => s.clone(),
+                v => v.to_string(),
+            };
+            text = text.replace(&format!("{{{}}}", name), &value);
+        }
+        Ok(handlebars::ScopedJson::Derived(serde_json::Value::String(
+            text,
+        )))
+    }
+}
+
+#[cfg(test)]
+mod i18n_tests {
+    use super::*;
+
+    #[test]
+    fn test_i18n_helper() {
+        let dir = "test_i18n";
+        std::fs::create_dir_all(dir).unwrap();
+        std::fs::write(
+            format!("{}/en.toml", dir),
+            "tags = \"Tags\"\nread = \"Read More\"\n[min_read]\none = \"{count} minute\"\nother = \"{count} minutes\"\n",
+        )
+        .unwrap();
+        std::fs::write(
+            format!("{}/zh.toml", dir),
+            "tags = \"标签\"\nmin_read = \"{count} 分钟\"\n",
+        )
+        .unwrap();
+        let mut i18n = I18n::new("en");
+        i18n.load_dir(dir).unwrap();
+        i18n.load_dir("not_exist_i18n").unwrap();
+        std::fs::remove_dir_all(dir).unwrap();
+
+        assert_eq!(i18n.translate("zh", "tags", None), Some("标签"));
+        // missing key falls back to default language
+        assert_eq!(i18n.translate("zh", "read", None), Some("Read More"));
+        assert_eq!(i18n.translate("fr", "tags", None), Some("Tags"));
+        assert_eq!(i18n.translate("en", "missing", None), None);
+
+        let mut reg = handlebars::Handlebars::new();
+        reg.register_helper("t", Box::new(i18n));
+        let render = |tpl: &str, data: serde_json::Value| reg.render_template(tpl, &data).unwrap();
+        let data = serde_json::json!({"site": {"language": "zh"}, "n": 5});
+        assert_eq!(render("{{t \"tags\"}}", data.clone()), "标签");
+        assert_eq!(render("{{t \"min_read\" count=n}}", data.clone()), "5 分钟");
+        assert_eq!(render("{{t \"tags\" lang=\"en\"}}", data), "Tags");
+        let data = serde_json::json!({"site": {"language": "en"}});
+        assert_eq!(
+            render("{{t \"min_read\" count=1}}", data.clone()),
+            "1 minute"
+        );
+        assert_eq!(
+            render("{{t \"min_read\" count=2}}", data.clone()),
+            "2 minutes"
+        );
+        assert_eq!(render("{{t \"missing\"}}", data), "missing");
+    }
+}
@@ -0,0 +1,162 @@
+use crate::models;
+
+// posts of one language, listings of the language are under /<lang>/
+#[derive(Debug)]
+pub struct Language {
+    pub code: String,
+    // url prefix of listings, like /zh
+    pub prefix: String,
+    pub posts_index: Vec<usize>,
+    // tags of posts in the language, posts_index of tags are indexes of all posts
+    pub tags: Vec<models::Tag>,
+}
+
+// other language version of a post
+#[derive(Debug, Default, Clone)]
+pub struct Translation {
+    pub language: String,
+    pub title: String,
+    pub url: String,
+}
+
+impl Language {
+    // languages with own listings, they are configured and not the default language.
+    // posts of other languages are listed in root listings
+    pub fn parse(posts: &[models::Post], config: &models::Config) -> Vec<Language> {
+        let mut languages = vec![];
+        for code in config.languages.keys() {
+            if *code == config.site.language {
+                continue;
+            }
+            let posts_index: Vec<usize> = posts
+                .iter()
+                .enumerate()
+                .filter(|(_, p)| p.meta.language.as_ref() == Some(code))
+                .map(|(i, _)| i)
+                .collect();
+            if posts_index.is_empty() {
+                continue;
+            }
+            let prefix = format!("/{}", code);
+            let tags = models::Tag::parse_by(
+                posts,
+                |p| {
+                    if p.meta.language.as_ref() == Some(code) {
+                        p.meta.tags.clone().unwrap_or_default()
+                    } else {
+                        vec![]
+                    }
+                },
+                &format!("{}{}", prefix, config.url.tag_link_format),
+                &format!("{}{}", prefix, config.url.tag_page_format),
+            );
+            languages.push(Language {
+                code: code.clone(),
+                prefix,
+                posts_index,
+                tags,
+            });
+        }
+        languages
+    }
+
+    pub fn contains(languages: &[Language], p: &models::Post) -> bool {
+        languages
+            .iter()
+            .any(|l| p.meta.language.as_ref() == Some(&l.code))
+    }
+}
+
+// link posts sharing the same translation_key, urls are root urls of posts
+pub fn link_translations(posts: &mut [models::Post], urls: &[String]) {
+    let mut groups: std::collections::HashMap<&str, Vec<usize>> = std::collections::HashMap::new();
+    for (index, p) in posts.iter().enumerate() {
+        if let Some(key) = p.meta.translation_key.as_deref() {
+            groups.entry(key).or_default().push(index);
+        }
+    }
+    let mut translations = vec![vec![]; posts.len()];
+    for indexes in groups.values() {
+        for index in indexes {
+            translations[*index] = indexes
+                .iter()
+                .filter(|i| *i != index)
+                .map(|i| Translation {
+                    language: posts[*i].meta.language.clone().unwrap_or_default(),
+                    title: posts[*i].meta.title.clone(),
+                    url: urls[*i].clone(),
+                })
+                .collect();
+        }
+    }
+    for (p, translations) in posts.iter_mut().zip(translations) {
+        p.translations = translations;
+    }
+}
+
+#[cfg(test)]
+mod language_tests {
+    use super::*;
+
+    fn build_post(language: &str, tags: &[&str], translation_key: Option<&str>) -> models::Post {
+        models::Post {
+            meta: models::PostMetadata {
+                title: format!("{} post", language),
+                language: Some(language.to_string()),
+                tags: Some(tags.iter().map(|t| t.to_string()).collect()),
+                translation_key: translation_key.map(|k| k.to_string()),
+                ..models::PostMetadata::default()
+            },
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_parse_languages() {
+        let mut config = models::Config::default();
+        config
+            .languages
+            .insert("en".to_string(), models::LanguageConfig::default());
+        config
+            .languages
+            .insert("zh".to_string(), models::LanguageConfig::default());
+        config
+            .languages
+            .insert("fr".to_string(), models::LanguageConfig::default());
+        let posts = vec![
+            build_post("en", &["rust"], None),
+            build_post("zh", &["rust", "blog"], None),
+            build_post("zh", &["rust"], None),
+        ];
+        let languages = Language::parse(&posts, &config);
+        // default language and languages without posts have no own listings
+        assert_eq!(languages.len(), 1);
+        assert_eq!(languages[0].code, "zh");
+        assert_eq!(languages[0].posts_index, vec![1, 2]);
+        assert_eq!(languages[0].tags[0].name, "rust");
+        assert_eq!(languages[0].tags[0].url, "/zh/tag/rust");
+        assert_eq!(languages[0].tags[0].posts_index, vec![1, 2]);
+        assert!(!Language::contains(&languages, &posts[0]));
+        assert!(Language::contains(&languages, &posts[1]));
+    }
+
+    #[test]
+    fn test_link_translations() {
+        let mut posts = vec![
+            build_post("en", &[], Some("hello")),
+            build_post("zh", &[], Some("hello")),
+            build_post("en", &[], None),
+        ];
+        let urls = vec![
+            "/hello".to_string(),
+            "/zh/hello".to_string(),
+            "/a".to_string(),
+        ];
+        link_translations(&mut posts, &urls);
+        assert_eq!(posts[0].translations.len(), 1);
+        assert_eq!(posts[0].translations[0].language, "zh");
+        assert_eq!(posts[0].translations[0].url, "/zh/hello");
+        assert_eq!(posts[1].translations[0].title, "en post");
+        assert!(posts[2].translations.is_empty());
+    }
+}
@@ -0,0 +1,115 @@
+use crate::models;
+use std::path::{Component, Path, PathBuf};
+
+// resolve . and .. of path without touching file system
+fn normalize_path(path: &Path) -> PathBuf {
+    let mut result = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                if matches!(result.components().next_back(), Some(Component::Normal(_))) {
+                    result.pop();
+                } else {
+                    result.push("..");
+                }
+            }
+            c => result.push(c),
+        }
+    }
+    result
+}
+
+// permalinks of content files, to resolve links between markdown files
+#[derive(Debug, Default)]
+pub struct ContentLinks {
+    source_dir: PathBuf,
+    urls: std::collections::HashMap<PathBuf, String>,
+}
+
+impl ContentLinks {
+    pub fn new(source_dir: &str) -> ContentLinks {
+        ContentLinks {
+            source_dir: PathBuf::from(source_dir),
+            urls: std::collections::HashMap::new(),
+        }
+    }
+
+    pub fn add(&mut self, local_file: &str, url: &str) {
+        self.urls
+            .insert(normalize_path(Path::new(local_file)), url.to_string());
+    }
+
+    // link like ../posts/foo.md or @/posts/foo.md#anchor is resolved to permalink,
+    // returns None if it is not a link to content file
+    pub fn resolve(&self, dest: &str, source_file: &str) -> Result<Option<String>, String> {
+        let (path, anchor) = match dest.split_once('#') {
+            Some((path, anchor)) => (path, Some(anchor)),
+            None => (dest, None),
+        };
+        let target = if let Some(path) = path.strip_prefix("@/") {
+            self.source_dir.join(path)
+        } else if path.ends_with(".md") && models::markdown::is_relative_link(path) {
+            Path::new(source_file)
+                .parent()
+                .unwrap_or_else(|| Path::new(""))
+                .join(path)
+        } else {
+            return Ok(None);
+        };
+        match self.urls.get(&normalize_path(&target)) {
+            Some(url) => match anchor {
+                Some(anchor) => Ok(Some(format!("{}#{}", url, anchor))),
+                None => Ok(Some(url.clone())),
+            },
+            None => Err(format!("content file is not found: {}", dest)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod links_tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_path() {
+        assert_eq!(
+            normalize_path(Path::new("source/posts/2021/../foo.md")),
+            PathBuf::from("source/posts/foo.md")
+        );
+        assert_eq!(
+            normalize_path(Path::new("./source/./pages/about.md")),
+            PathBuf::from("source/pages/about.md")
+        );
+        assert_eq!(
+            normalize_path(Path::new("../a.md")),
+            PathBuf::from("../a.md")
+        );
+    }
+
+    #[test]
+    fn test_resolve_links() {
+        let mut links = ContentLinks::new("source");
+        links.add("source/posts/2021/foo.md", "/2021/01/02/foo");
+        links.add("source/pages/about.md", "/about");
+
+        let source = "source/posts/bar.md";
+        assert_eq!(
+            links.resolve("2021/foo.md", source),
+            Ok(Some("/2021/01/02/foo".to_string()))
+        );
+        assert_eq!(
+            links.resolve("../pages/about.md#contact", source),
+            Ok(Some("/about#contact".to_string()))
+        );
+        assert_eq!(
+            links.resolve("@/posts/2021/foo.md#intro", source),
+            Ok(Some("/2021/01/02/foo#intro".to_string()))
+        );
+        assert_eq!(links.resolve("https://example.com/a.md", source), Ok(None));
+        assert_eq!(links.resolve("cover.png", source), Ok(None));
+        assert_eq!(links.resolve("#top", source), Ok(None));
+        assert!(links.resolve("missing.md", source).is_err());
+        assert!(links.resolve("@/posts/missing.md", source).is_err());
+    }
+}
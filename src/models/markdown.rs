@@ -0,0 +1,978 @@
+use crate::models;
+use crate::utils;
+use pulldown_cmark as cmark;
+
+#[derive(Debug, Default, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct TocItem {
+    pub level: u32,
+    pub text: String,
+    pub anchor: String,
+    pub children: Vec<TocItem>,
+}
+
+#[derive(Debug, Default)]
+pub struct Markdown {
+    pub html: String,
+    pub toc: Vec<TocItem>,
+}
+
+fn heading_level_number(level: cmark::HeadingLevel) -> u32 {
+    match level {
+        cmark::HeadingLevel::H1 => 1,
+        cmark::HeadingLevel::H2 => 2,
+        cmark::HeadingLevel::H3 => 3,
+        cmark::HeadingLevel::H4 => 4,
+        cmark::HeadingLevel::H5 => 5,
+        cmark::HeadingLevel::H6 => 6,
+    }
+}
+
+// keep letters and digits of any language, join words with '-'
+fn build_heading_anchor(text: &str) -> String {
+    let mut anchor = String::new();
+    for c in text.trim().chars() {
+        if c.is_alphanumeric() || c == '_' {
+            anchor.extend(c.to_lowercase());
+        } else if (c.is_whitespace() || c == '-') && !anchor.ends_with('-') {
+            anchor.push('-');
+        }
+    }
+    let anchor = anchor.trim_matches('-').to_string();
+    if anchor.is_empty() {
+        return "section".to_string();
+    }
+    anchor
+}
+
+// build nested toc from flat headings, lower level heading is child of previous higher one
+fn build_toc_tree(headings: Vec<TocItem>) -> Vec<TocItem> {
+    let mut roots: Vec<TocItem> = vec![];
+    for heading in headings {
+        let mut siblings = &mut roots;
+        while siblings
+            .last()
+            .is_some_and(|last| last.level < heading.level)
+        {
+            siblings = &mut siblings.last_mut().unwrap().children;
+        }
+        siblings.push(heading);
+    }
+    roots
+}
+
+// attributes of fenced code info string, such as: rust title="main.rs" {3-5} linenos
+#[derive(Debug, Default, PartialEq)]
+pub struct CodeFence {
+    pub lang: String,
+    pub title: Option<String>,
+    pub highlight_lines: Vec<std::ops::RangeInclusive<usize>>,
+    pub line_numbers: bool,
+}
+
+fn split_info_string(info: &str) -> Vec<String> {
+    let mut tokens = vec![];
+    let mut current = String::new();
+    let mut quote: Option<char> = None;
+    // whitespace and commas inside braces belong to line ranges, like {3-5, 7}
+    let mut braces = 0;
+    for c in info.chars() {
+        match quote {
+            Some(q) if c == q => quote = None,
+            Some(_) => current.push(c),
+            None if c == '"' || c == '\'' => quote = Some(c),
+            None if c == '{' => {
+                braces += 1;
+                current.push(c);
+            }
+            None if c == '}' && braces > 0 => {
+                braces -= 1;
+                current.push(c);
+            }
+            None if braces == 0 && (c.is_whitespace() || c == ',') => {
+                if !current.is_empty() {
+                    tokens.push(std::mem::take(&mut current));
+                }
+            }
+            None => current.push(c),
+        }
+    }
+    if !current.is_empty() {
+        tokens.push(current);
+    }
+    tokens
+}
+
+// parse line ranges like 1,3-5, reversed range like 5-3 is same as 3-5
+fn parse_line_ranges(ranges: &str) -> Vec<std::ops::RangeInclusive<usize>> {
+    let mut lines = vec![];
+    for range in ranges.split(',') {
+        let range = range.trim();
+        let (start, end) = match range.split_once('-') {
+            Some((start, end)) => (start.trim().parse::<usize>(), end.trim().parse()),
+            None => (range.parse::<usize>(), range.parse()),
+        };
+        if let (Ok(start), Ok(end)) = (start, end) {
+            lines.push(start.min(end)..=start.max(end));
+        }
+    }
+    lines
+}
+
+impl CodeFence {
+    pub fn parse(info: &str) -> CodeFence {
+        let mut fence = CodeFence::default();
+        for (i, token) in split_info_string(info).into_iter().enumerate() {
+            if token.starts_with('{') && token.ends_with('}') {
+                fence.highlight_lines = parse_line_ranges(&token[1..token.len() - 1]);
+                continue;
+            }
+            match token.split_once('=') {
+                Some(("title", value)) => fence.title = Some(value.to_string()),
+                Some(("hl_lines", value)) => fence.highlight_lines = parse_line_ranges(value),
+                Some(("linenos", value)) => fence.line_numbers = value != "false",
+                Some(_) => {}
+                None if token == "linenos" => fence.line_numbers = true,
+                None if i == 0 => fence.lang = token,
+                None => {}
+            }
+        }
+        fence
+    }
+
+    fn is_highlighted(&self, line_number: usize) -> bool {
+        self.highlight_lines
+            .iter()
+            .any(|range| range.contains(&line_number))
+    }
+
+    fn has_attributes(&self) -> bool {
+        self.title.is_some() || !self.highlight_lines.is_empty() || self.line_numbers
+    }
+}
+
+// plain text of markdown, markup is stripped
+pub fn markdown_to_text(content: &str) -> String {
+    let mut text = String::new();
+    for event in cmark::Parser::new_ext(content, cmark::Options::all()) {
+        match event {
+            cmark::Event::Text(t) | cmark::Event::Code(t) => text.push_str(&t),
+            cmark::Event::SoftBreak | cmark::Event::HardBreak => text.push('\n'),
+            cmark::Event::End(_) => text.push('\n'),
+            _ => {}
+        }
+    }
+    text
+}
+
+// options to render one source file
+#[derive(Default, Clone)]
+pub struct RenderOptions<'a> {
+    // prefix of relative links and images, used by page bundles
+    pub link_base: Option<String>,
+    // links to other content files are resolved to permalinks
+    pub content_links: Option<&'a models::ContentLinks>,
+    // shortcodes are rendered by templates in shortcodes directory of theme
+    pub theme: Option<&'a models::Theme<'a>>,
+    // convert latex math to mathml
+    pub math: bool,
+    pub source_file: String,
+    // lines of front matter before content, to report line of source file
+    pub line_offset: usize,
+    // markdown extensions, site config with overrides of the post
+    pub markdown: models::MarkdownConfig,
+}
+
+// ranges of code blocks and inline code, shortcodes and math in them are kept as text
+pub fn parse_code_ranges(content: &str) -> Vec<std::ops::Range<usize>> {
+    let mut ranges = vec![];
+    let mut code_start = None;
+    let parser = cmark::Parser::new_ext(content, cmark::Options::all());
+    for (event, range) in parser.into_offset_iter() {
+        match event {
+            cmark::Event::Start(cmark::Tag::CodeBlock(_)) => code_start = Some(range.start),
+            cmark::Event::End(cmark::Tag::CodeBlock(_)) => {
+                ranges.push(code_start.take().unwrap_or(range.start)..range.end)
+            }
+            cmark::Event::Code(_) => ranges.push(range),
+            _ => {}
+        }
+    }
+    ranges
+}
+
+// placeholder is kept by markdown parser, and replaced by html after rendering
+fn build_placeholder(index: usize) -> String {
+    format!("PUGOPLACEHOLDER{}END", index)
+}
+
+// plain text of html, tags are dropped and whitespace is collapsed
+fn html_to_text(html: &str) -> String {
+    let mut text = String::new();
+    let mut in_tag = false;
+    for c in html.chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' if in_tag => {
+                in_tag = false;
+                text.push(' ');
+            }
+            _ if !in_tag => text.push(c),
+            _ => {}
+        }
+    }
+    let text = text.split_whitespace().collect::<Vec<&str>>().join(" ");
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&amp;", "&")
+}
+
+// placeholders in heading text are replaced by plain text of their html, for toc and anchor
+fn resolve_placeholders(text: &str, placeholders: &[String]) -> String {
+    if !text.contains("PUGOPLACEHOLDER") {
+        return text.to_string();
+    }
+    let mut resolved = text.to_string();
+    for (i, html) in placeholders.iter().enumerate() {
+        let placeholder = build_placeholder(i);
+        if resolved.contains(&placeholder) {
+            resolved = resolved.replace(&placeholder, &html_to_text(html));
+        }
+    }
+    // placeholders of empty html leave double spaces
+    resolved.split_whitespace().collect::<Vec<&str>>().join(" ")
+}
+
+// replace ranges of content with placeholders, lines are kept to report line number of errors
+fn replace_with_placeholders(
+    content: &str,
+    ranges: &[std::ops::Range<usize>],
+    start_index: usize,
+) -> String {
+    let mut replaced = String::new();
+    let mut offset = 0;
+    for (i, range) in ranges.iter().enumerate() {
+        replaced.push_str(&content[offset..range.start]);
+        replaced.push_str(&build_placeholder(start_index + i));
+        replaced.push_str(&"\n".repeat(content[range.clone()].matches('\n').count()));
+        offset = range.end;
+    }
+    replaced.push_str(&content[offset..]);
+    replaced
+}
+
+pub fn is_relative_link(dest: &str) -> bool {
+    if dest.is_empty() || dest.starts_with(['/', '#', '?']) {
+        return false;
+    }
+    // skip links with scheme, like https: or mailto:
+    match dest.find(':') {
+        Some(i) => dest[..i].contains(['/', '?', '#']),
+        None => true,
+    }
+}
+
+fn rewrite_link<'a>(
+    dest: cmark::CowStr<'a>,
+    options: &RenderOptions,
+) -> Result<cmark::CowStr<'a>, String> {
+    if let Some(links) = options.content_links {
+        if let Some(url) = links.resolve(&dest, &options.source_file)? {
+            return Ok(url.into());
+        }
+    }
+    match &options.link_base {
+        Some(base) if is_relative_link(&dest) => {
+            let dest = dest.strip_prefix("./").unwrap_or(&dest);
+            Ok(format!("{}/{}", base.trim_end_matches('/'), dest).into())
+        }
+        _ => Ok(dest),
+    }
+}
+
+// replace math with placeholders, mathml of each math is pushed to placeholders
+fn expand_math(
+    content: &str,
+    render_options: &RenderOptions,
+    placeholders: &mut Vec<String>,
+) -> Result<String, Box<dyn std::error::Error>> {
+    let start_index = placeholders.len();
+    let mut ranges = vec![];
+    for span in models::math::parse_math_spans(content) {
+        let mathml =
+            models::math::latex_to_mathml(span.latex(content), span.display).map_err(|e| {
+                let line = render_options.line_offset
+                    + content[..span.range.start].matches('\n').count()
+                    + 1;
+                format!("{} in {}:{}", e, render_options.source_file, line)
+            })?;
+        placeholders.push(mathml);
+        ranges.push(span.range);
+    }
+    Ok(replace_with_placeholders(content, &ranges, start_index))
+}
+
+#[derive(Default)]
+pub struct MarkdownRenderer {
+    highlighter: Option<models::Highlighter>,
+    // host of site url, links to other hosts are external
+    site_host: Option<String>,
+}
+
+impl MarkdownRenderer {
+    pub fn new(config: &models::Config) -> Result<MarkdownRenderer, Box<dyn std::error::Error>> {
+        let mut renderer = MarkdownRenderer::default();
+        if config.highlight.enabled {
+            renderer.highlighter = Some(models::Highlighter::new(&config.highlight)?);
+        }
+        renderer.site_host = utils::url_host(&config.url.base).map(|h| h.to_string());
+        Ok(renderer)
+    }
+
+    pub fn get_highlighter(&self) -> Option<&models::Highlighter> {
+        self.highlighter.as_ref()
+    }
+
+    pub fn render(
+        &self,
+        content: &str,
+        render_options: &RenderOptions,
+    ) -> Result<Markdown, Box<dyn std::error::Error>> {
+        // html of shortcodes and math, they are replaced by placeholders before parsing markdown
+        let mut placeholders = vec![];
+        let mut content = self.expand_containers(content, render_options, &mut placeholders)?;
+        if let Some(theme) = render_options.theme {
+            content = self.expand_shortcodes(&content, theme, render_options, &mut placeholders)?;
+        }
+        if render_options.math {
+            content = expand_math(&content, render_options, &mut placeholders)?;
+        }
+        let content = content.as_str();
+        let markdown = &render_options.markdown;
+        let mut options = cmark::Options::ENABLE_FOOTNOTES
+            | cmark::Options::ENABLE_TABLES
+            | cmark::Options::ENABLE_STRIKETHROUGH
+            | cmark::Options::ENABLE_TASKLISTS;
+        if markdown.smart_punctuation {
+            options |= cmark::Options::ENABLE_SMART_PUNCTUATION;
+        }
+        if markdown.heading_attributes {
+            options |= cmark::Options::ENABLE_HEADING_ATTRIBUTES;
+        }
+        let parser = cmark::Parser::new_ext(content, options);
+
+        // add unique id to each heading, collect headings as toc
+        let mut events = vec![];
+        let mut headings = vec![];
+        let mut used_anchors = std::collections::HashSet::new();
+        let mut heading_events: Option<Vec<cmark::Event>> = None;
+        let mut code_events: Option<Vec<cmark::Event>> = None;
+        for (event, range) in parser.into_offset_iter() {
+            let source_error = |e: String| {
+                let line =
+                    render_options.line_offset + content[..range.start].matches('\n').count() + 1;
+                format!("{} in {}:{}", e, render_options.source_file, line)
+            };
+            let event = match event {
+                cmark::Event::Start(cmark::Tag::Link(kind, dest, title)) => {
+                    let dest = rewrite_link(dest, render_options).map_err(source_error)?;
+                    match self.build_external_link(&dest, &title, markdown) {
+                        Some(html) => cmark::Event::Html(html.into()),
+                        None => cmark::Event::Start(cmark::Tag::Link(kind, dest, title)),
+                    }
+                }
+                cmark::Event::Start(cmark::Tag::Image(kind, dest, title)) => {
+                    let dest = rewrite_link(dest, render_options).map_err(source_error)?;
+                    cmark::Event::Start(cmark::Tag::Image(kind, dest, title))
+                }
+                cmark::Event::Html(_) if !markdown.unsafe_html => continue,
+                cmark::Event::SoftBreak if markdown.hard_breaks => cmark::Event::HardBreak,
+                _ => event,
+            };
+            match event {
+                cmark::Event::Start(cmark::Tag::Heading(..)) => {
+                    heading_events = Some(vec![event]);
+                }
+                cmark::Event::End(cmark::Tag::Heading(level, id, classes)) => {
+                    let inner = heading_events.take().unwrap_or_default();
+                    let mut text = String::new();
+                    for e in &inner {
+                        if let cmark::Event::Text(t) | cmark::Event::Code(t) = e {
+                            text.push_str(t);
+                        }
+                    }
+                    let text = resolve_placeholders(&text, &placeholders);
+                    let mut anchor = match id {
+                        Some(id) => id.to_string(),
+                        None => build_heading_anchor(&text),
+                    };
+                    // suffix may also be taken by another heading, like Usage 1
+                    let mut count = 1;
+                    let base = anchor.clone();
+                    while used_anchors.contains(&anchor) {
+                        anchor = format!("{}-{}", base, count);
+                        count += 1;
+                    }
+                    used_anchors.insert(anchor.clone());
+
+                    let level = heading_level_number(level);
+                    let mut start_tag = format!("<h{} id=\"", level);
+                    cmark::escape::escape_html(&mut start_tag, &anchor).unwrap();
+                    start_tag.push('"');
+                    if !classes.is_empty() {
+                        start_tag.push_str(" class=\"");
+                        cmark::escape::escape_html(&mut start_tag, &classes.join(" ")).unwrap();
+                        start_tag.push('"');
+                    }
+                    start_tag.push('>');
+                    events.push(cmark::Event::Html(start_tag.into()));
+                    events.extend(inner.into_iter().skip(1));
+                    events.push(cmark::Event::Html(format!("</h{}>\n", level).into()));
+
+                    headings.push(TocItem {
+                        level,
+                        text: text.trim().to_string(),
+                        anchor,
+                        children: vec![],
+                    });
+                }
+                // collect code block, highlight it when all code text is read
+                cmark::Event::Start(cmark::Tag::CodeBlock(_)) => {
+                    code_events = Some(vec![event]);
+                }
+                cmark::Event::End(cmark::Tag::CodeBlock(ref kind)) => {
+                    let mut inner = code_events.take().unwrap_or_default();
+                    let mut code = String::new();
+                    for e in &inner {
+                        if let cmark::Event::Text(t) = e {
+                            code.push_str(t);
+                        }
+                    }
+                    let fence = match kind {
+                        cmark::CodeBlockKind::Fenced(info) => CodeFence::parse(info),
+                        cmark::CodeBlockKind::Indented => CodeFence::default(),
+                    };
+                    if fence.lang == "math" && render_options.math {
+                        let mathml =
+                            models::math::latex_to_mathml(&code, true).map_err(source_error)?;
+                        events.push(cmark::Event::Html(mathml.into()));
+                        continue;
+                    }
+                    match self.render_code_block(&fence, &code) {
+                        Some(html) => events.push(cmark::Event::Html(html.into())),
+                        None => {
+                            inner.push(event);
+                            events.extend(inner);
+                        }
+                    }
+                }
+                _ => {
+                    if let Some(inner) = code_events.as_mut() {
+                        inner.push(event);
+                    } else if let Some(inner) = heading_events.as_mut() {
+                        inner.push(event);
+                    } else {
+                        events.push(event);
+                    }
+                }
+            }
+        }
+
+        let mut events = models::admonition::convert_alerts(events);
+        if markdown.definition_lists {
+            events = models::deflist::convert_definition_lists(events);
+        }
+        let mut buf = String::new();
+        cmark::html::push_html(&mut buf, events.into_iter());
+        for (i, html) in placeholders.iter().enumerate() {
+            let placeholder = build_placeholder(i);
+            buf = buf
+                .replace(&format!("<p>{}</p>\n", placeholder), html)
+                .replace(&placeholder, html);
+        }
+        Ok(Markdown {
+            html: buf,
+            toc: build_toc_tree(headings),
+        })
+    }
+
+    // start tag of link to other host, None if no attribute is needed
+    fn build_external_link(
+        &self,
+        dest: &str,
+        title: &str,
+        markdown: &models::MarkdownConfig,
+    ) -> Option<String> {
+        let host = utils::url_host(dest)?;
+        if self.site_host.as_deref() == Some(host) {
+            return None;
+        }
+        let mut rel = vec![];
+        if markdown.external_links_no_follow {
+            rel.push("nofollow");
+        }
+        if markdown.external_links_target_blank {
+            rel.push("noopener");
+        }
+        if markdown.external_links_no_referrer {
+            rel.push("noreferrer");
+        }
+        if rel.is_empty() {
+            return None;
+        }
+        let mut html = String::from("<a href=\"");
+        cmark::escape::escape_href(&mut html, dest).unwrap();
+        html.push('"');
+        if !title.is_empty() {
+            html.push_str(" title=\"");
+            cmark::escape::escape_html(&mut html, title).unwrap();
+            html.push('"');
+        }
+        if markdown.external_links_target_blank {
+            html.push_str(" target=\"_blank\"");
+        }
+        html.push_str(&format!(" rel=\"{}\">", rel.join(" ")));
+        Some(html)
+    }
+
+    // replace :::kind containers with placeholders, html of each container is pushed to placeholders
+    fn expand_containers(
+        &self,
+        content: &str,
+        render_options: &RenderOptions,
+        placeholders: &mut Vec<String>,
+    ) -> Result<String, Box<dyn std::error::Error>> {
+        let start_index = placeholders.len();
+        let mut ranges = vec![];
+        for container in models::admonition::parse_containers(content) {
+            let body_options = RenderOptions {
+                line_offset: render_options.line_offset
+                    + content[..container.body.start].matches('\n').count(),
+                ..render_options.clone()
+            };
+            let body_html = self.render(&content[container.body], &body_options)?.html;
+            let mut html = models::admonition::build_admonition_start(
+                &container.kind,
+                container.title.as_deref(),
+            );
+            html.push_str(&body_html);
+            html.push_str(models::admonition::ADMONITION_END);
+            placeholders.push(html);
+            ranges.push(container.range);
+        }
+        Ok(replace_with_placeholders(content, &ranges, start_index))
+    }
+
+    // replace shortcodes with placeholders, html of each shortcode is pushed to placeholders
+    fn expand_shortcodes(
+        &self,
+        content: &str,
+        theme: &models::Theme,
+        render_options: &RenderOptions,
+        placeholders: &mut Vec<String>,
+    ) -> Result<String, Box<dyn std::error::Error>> {
+        let shortcodes = models::shortcode::parse_shortcodes(content)
+            .map_err(|e| format!("{} in {}", e, render_options.source_file))?;
+        let start_index = placeholders.len();
+        let mut ranges = vec![];
+        for shortcode in shortcodes {
+            let mut vars = shortcode.args;
+            if let Some(body) = shortcode.body {
+                let body_options = RenderOptions {
+                    line_offset: render_options.line_offset
+                        + content[..body.start].matches('\n').count(),
+                    ..render_options.clone()
+                };
+                let body = &content[body];
+                let body_html = self.render(body, &body_options)?.html;
+                vars.insert(
+                    "inner".to_string(),
+                    serde_json::Value::String(body.to_string()),
+                );
+                vars.insert("body".to_string(), serde_json::Value::String(body_html));
+            }
+            let html = theme
+                .render_shortcode(&shortcode.name, &serde_json::Value::Object(vars))
+                .map_err(|e| format!("{} in {}", e, render_options.source_file))?;
+            placeholders.push(html);
+            ranges.push(shortcode.range);
+        }
+        Ok(replace_with_placeholders(content, &ranges, start_index))
+    }
+
+    fn render_code_block(&self, fence: &CodeFence, code: &str) -> Option<String> {
+        let highlighted = match self.highlighter.as_ref() {
+            Some(highlighter) if !fence.lang.is_empty() => {
+                highlighter.highlight_lines(code, &fence.lang)
+            }
+            _ => None,
+        };
+        // keep default output of pulldown-cmark if nothing to do
+        if highlighted.is_none() && !fence.has_attributes() {
+            return None;
+        }
+        let lines = highlighted.unwrap_or_else(|| {
+            code.lines()
+                .map(|line| {
+                    let mut escaped = String::new();
+                    cmark::escape::escape_html(&mut escaped, line).unwrap();
+                    escaped
+                })
+                .collect()
+        });
+
+        let mut html = String::new();
+        if fence.has_attributes() {
+            html.push_str("<figure class=\"code-block\">");
+            if let Some(title) = &fence.title {
+                html.push_str("<figcaption class=\"code-title\">");
+                cmark::escape::escape_html(&mut html, title).unwrap();
+                html.push_str("</figcaption>");
+            }
+        }
+        match self.highlighter.as_ref() {
+            Some(highlighter) => {
+                html.push_str(&format!("<pre {}>", highlighter.pre_attributes()));
+            }
+            None => html.push_str("<pre>"),
+        }
+        html.push_str("<code");
+        if !fence.lang.is_empty() {
+            html.push_str(" class=\"language-");
+            cmark::escape::escape_html(&mut html, &fence.lang).unwrap();
+            html.push('"');
+        }
+        html.push('>');
+        for (i, line) in lines.iter().enumerate() {
+            if !fence.has_attributes() {
+                html.push_str(line);
+                html.push('\n');
+                continue;
+            }
+            let line_number = i + 1;
+            if fence.is_highlighted(line_number) {
+                html.push_str("<span class=\"line line-highlight\">");
+            } else {
+                html.push_str("<span class=\"line\">");
+            }
+            if fence.line_numbers {
+                html.push_str(&format!(
+                    "<span class=\"line-number\">{}</span>",
+                    line_number
+                ));
+            }
+            html.push_str(line);
+            html.push_str("</span>\n");
+        }
+        html.push_str("</code></pre>\n");
+        if fence.has_attributes() {
+            html.push_str("</figure>\n");
+        }
+        Some(html)
+    }
+}
+
+#[cfg(test)]
+mod markdown_tests {
+    use super::*;
+
+    #[test]
+    fn test_heading_anchor() {
+        assert_eq!(build_heading_anchor("Hello World"), "hello-world");
+        assert_eq!(
+            build_heading_anchor(" What's new in v1.2? "),
+            "whats-new-in-v12"
+        );
+        assert_eq!(build_heading_anchor("中文 标题"), "中文-标题");
+        assert_eq!(build_heading_anchor("!!!"), "section");
+    }
+
+    #[test]
+    fn test_render_toc() {
+        let content = "# Title\n\n## Install `pugo`\n\ntext\n\n### From source\n\n## Usage\n\n## Usage\n\n## Usage 1\n";
+        let md = MarkdownRenderer::default()
+            .render(content, &RenderOptions::default())
+            .unwrap();
+        assert!(md.html.contains("<h1 id=\"title\">Title</h1>"));
+        assert!(md
+            .html
+            .contains("<h2 id=\"install-pugo\">Install <code>pugo</code></h2>"));
+        assert!(md.html.contains("<h2 id=\"usage\">Usage</h2>"));
+        assert!(md.html.contains("<h2 id=\"usage-1\">Usage</h2>"));
+
+        assert_eq!(md.toc.len(), 1);
+        assert_eq!(md.toc[0].text, "Title");
+        let children = &md.toc[0].children;
+        assert_eq!(children.len(), 4);
+        assert_eq!(children[0].text, "Install pugo");
+        assert_eq!(children[0].level, 2);
+        assert_eq!(children[0].children[0].anchor, "from-source");
+        assert_eq!(children[2].anchor, "usage-1");
+        assert_eq!(children[3].anchor, "usage-1-1");
+        assert!(md.html.contains("<h2 id=\"usage-1-1\">Usage 1</h2>"));
+    }
+
+    #[test]
+    fn test_render_highlight() {
+        let content = "```rust\nfn main() {}\n```\n\n```unknown\nabc\n```\n";
+        let md = MarkdownRenderer::default()
+            .render(content, &RenderOptions::default())
+            .unwrap();
+        assert!(md
+            .html
+            .contains("<pre><code class=\"language-rust\">fn main() {}\n</code></pre>"));
+
+        let config = models::Config::default();
+        let md = MarkdownRenderer::new(&config)
+            .unwrap()
+            .render(content, &RenderOptions::default())
+            .unwrap();
+        assert!(md
+            .html
+            .contains("<pre class=\"highlight hl-code\"><code class=\"language-rust\"><span"));
+        assert!(md
+            .html
+            .contains("<pre><code class=\"language-unknown\">abc\n</code></pre>"));
+    }
+
+    #[test]
+    fn test_code_fence() {
+        let fence = CodeFence::parse("rust title=\"main.rs\" {3-5,7} linenos");
+        assert_eq!(fence.lang, "rust");
+        assert_eq!(fence.title, Some("main.rs".to_string()));
+        assert_eq!(fence.highlight_lines, vec![3..=5, 7..=7]);
+        assert!(fence.line_numbers);
+
+        let fence = CodeFence::parse("title='my file.go' hl_lines=2");
+        assert_eq!(fence.lang, "");
+        assert_eq!(fence.title, Some("my file.go".to_string()));
+        assert_eq!(fence.highlight_lines, vec![2..=2]);
+        assert!(!fence.line_numbers);
+
+        assert_eq!(CodeFence::parse("python").lang, "python");
+
+        // spaces inside braces do not split line ranges
+        let fence = CodeFence::parse("rust {3-5, 7} linenos");
+        assert_eq!(fence.lang, "rust");
+        assert_eq!(fence.highlight_lines, vec![3..=5, 7..=7]);
+        assert!(fence.line_numbers);
+
+        // reversed range is accepted, large range is not expanded to lines
+        let fence = CodeFence::parse("rust {5-3,1-4000000000}");
+        assert_eq!(fence.highlight_lines, vec![3..=5, 1..=4000000000]);
+        assert!(fence.is_highlighted(4));
+        assert!(fence.is_highlighted(4000000000));
+        assert!(!fence.is_highlighted(4000000001));
+    }
+
+    #[test]
+    fn test_render_code_fence() {
+        let content = "```text title=\"a.txt\" {2} linenos\nfirst\n<second>\n```\n";
+        let md = MarkdownRenderer::default()
+            .render(content, &RenderOptions::default())
+            .unwrap();
+        assert!(md.html.contains(
+            "<figure class=\"code-block\"><figcaption class=\"code-title\">a.txt</figcaption>"
+        ));
+        assert!(md
+            .html
+            .contains("<span class=\"line\"><span class=\"line-number\">1</span>first</span>\n"));
+        assert!(md.html.contains("<span class=\"line line-highlight\"><span class=\"line-number\">2</span>&lt;second&gt;</span>\n"));
+    }
+
+    #[test]
+    fn test_render_link_base() {
+        let renderer = MarkdownRenderer::default();
+        let options = RenderOptions {
+            link_base: Some("/2022/05/post/".to_string()),
+            ..Default::default()
+        };
+        let content = "![cover](cover.png) [file](./files/a.zip) [abs](/about) [ext](https://example.com) [mail](mailto:a@b.c) [top](#top)";
+        let html = renderer.render(content, &options).unwrap().html;
+        assert!(html.contains("src=\"/2022/05/post/cover.png\""));
+        assert!(html.contains("href=\"/2022/05/post/files/a.zip\""));
+        assert!(html.contains("href=\"/about\""));
+        assert!(html.contains("href=\"https://example.com\""));
+        assert!(html.contains("href=\"mailto:a@b.c\""));
+        assert!(html.contains("href=\"#top\""));
+        // no base, links are kept
+        assert!(renderer
+            .render(content, &RenderOptions::default())
+            .unwrap()
+            .html
+            .contains("src=\"cover.png\""));
+    }
+
+    #[test]
+    fn test_render_content_links() {
+        let mut links = models::ContentLinks::new("source");
+        links.add("source/posts/foo.md", "/2021/foo");
+        let options = RenderOptions {
+            content_links: Some(&links),
+            source_file: "source/posts/bar.md".to_string(),
+            line_offset: 4,
+            ..Default::default()
+        };
+        let renderer = MarkdownRenderer::default();
+        let html = renderer
+            .render(
+                "[foo](foo.md#intro) and [ref]\n\n[ref]: @/posts/foo.md",
+                &options,
+            )
+            .unwrap()
+            .html;
+        assert!(html.contains("href=\"/2021/foo#intro\""));
+        assert!(html.contains("href=\"/2021/foo\""));
+
+        let err = renderer
+            .render("line\n\n[missing](missing.md)", &options)
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "content file is not found: missing.md in source/posts/bar.md:7"
+        );
+    }
+
+    #[test]
+    fn test_render_shortcodes() {
+        std::fs::create_dir_all("test_shortcode_theme/shortcodes").unwrap();
+        std::fs::write(
+            "test_shortcode_theme/shortcodes/box.hbs",
+            "<div class=\"box {{kind}}\">{{{body}}}</div>",
+        )
+        .unwrap();
+        std::fs::write(
+            "test_shortcode_theme/shortcodes/icon.html",
+            "<i class=\"icon-{{args.[0]}}\"></i>",
+        )
+        .unwrap();
+        let theme =
+            models::Theme::parse("test_shortcode_theme", &models::Config::default()).unwrap();
+        let options = RenderOptions {
+            theme: Some(&theme),
+            ..Default::default()
+        };
+        let renderer = MarkdownRenderer::default();
+        let content = "Star {{< icon star >}} here\n\n{{< box kind=\"tip\" >}}\n**bold** {{< icon ok >}}\n{{< /box >}}\n\n`{{< icon code >}}`";
+        let html = renderer.render(content, &options).unwrap().html;
+        assert!(html.contains("<p>Star <i class=\"icon-star\"></i> here</p>"));
+        assert!(html.contains(
+            "<div class=\"box tip\"><p><strong>bold</strong> <i class=\"icon-ok\"></i></p>\n</div>"
+        ));
+        assert!(html.contains("<code>{{&lt; icon code &gt;}}</code>"));
+        assert!(renderer.render("{{< missing >}}", &options).is_err());
+
+        // toc and anchor of heading use plain text of shortcode html
+        let md = renderer
+            .render(
+                "## Star {{< icon star >}} and {{< box >}}Tom & Jerry{{< /box >}}",
+                &options,
+            )
+            .unwrap();
+        assert_eq!(md.toc[0].text, "Star and Tom & Jerry");
+        assert_eq!(md.toc[0].anchor, "star-and-tom-jerry");
+        assert!(!md.html.contains("PUGOPLACEHOLDER"));
+
+        std::fs::remove_dir_all("test_shortcode_theme").unwrap();
+    }
+
+    #[test]
+    fn test_render_math() {
+        let renderer = MarkdownRenderer::default();
+        let content =
+            "Inline $x_1^2$ and *em*\n\n$$\n\\frac{a}{b}\n$$\n\n```math\ny = x\n```\n\n`$a$`";
+        let options = RenderOptions {
+            math: true,
+            ..Default::default()
+        };
+        let html = renderer.render(content, &options).unwrap().html;
+        assert!(html.contains("<p>Inline <math"));
+        assert!(html.contains("<em>em</em>"));
+        assert!(html.contains("<mfrac>"));
+        assert_eq!(html.matches("display=\"block\"").count(), 2);
+        assert!(html.contains("<code>$a$</code>"));
+
+        // toc and anchor of heading use plain text of math
+        let md = renderer.render("## Area $x^2$", &options).unwrap();
+        assert_eq!(md.toc[0].text, "Area x 2");
+        assert_eq!(md.toc[0].anchor, "area-x-2");
+        assert!(md.html.contains("<h2 id=\"area-x-2\">Area <math"));
+
+        // math is disabled
+        let html = renderer
+            .render(content, &RenderOptions::default())
+            .unwrap()
+            .html;
+        assert!(!html.contains("<math"));
+
+        let options = RenderOptions {
+            math: true,
+            source_file: "a.md".to_string(),
+            ..Default::default()
+        };
+        let err = renderer
+            .render("line\n\n$\\frac{1}$", &options)
+            .unwrap_err();
+        assert!(err.to_string().ends_with("in a.md:3"));
+    }
+
+    #[test]
+    fn test_render_admonitions() {
+        let renderer = MarkdownRenderer::default();
+        let content = "> [!NOTE]\n> Useful *information*.\n\n> [!WARNING] Careful\n> > [!TIP]\n> > nested\n\n> normal quote\n\n:::tip\n**Tip** body\n:::";
+        let html = renderer
+            .render(content, &RenderOptions::default())
+            .unwrap()
+            .html;
+        assert!(html.contains("<aside class=\"admonition note\"><p class=\"admonition-title\">Note</p>\n<p>Useful <em>information</em>.</p>\n</aside>"));
+        assert!(html.contains("<aside class=\"admonition warning\"><p class=\"admonition-title\">Careful</p>\n<aside class=\"admonition tip\">"));
+        assert!(html.contains("<blockquote>\n<p>normal quote</p>\n</blockquote>"));
+        assert!(html.contains("<aside class=\"admonition tip\"><p class=\"admonition-title\">Tip</p>\n<p><strong>Tip</strong> body</p>\n</aside>"));
+    }
+
+    #[test]
+    fn test_render_markdown_config() {
+        let mut config = models::Config::default();
+        config.url.base = "https://example.com".to_string();
+        let renderer = MarkdownRenderer::new(&config).unwrap();
+        let options = RenderOptions {
+            markdown: models::MarkdownConfig {
+                smart_punctuation: true,
+                heading_attributes: true,
+                definition_lists: true,
+                hard_breaks: true,
+                unsafe_html: false,
+                external_links_target_blank: true,
+                external_links_no_follow: true,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let content = "# Title {#intro .big}\n\n\"Quoted\" -- text<b>bold</b>\nnext\n\nTerm\n: Meaning\n\n<div>raw</div>\n\n[out](https://rust-lang.org \"Rust\") [in](https://example.com/a)";
+        let html = renderer.render(content, &options).unwrap().html;
+        assert!(html.contains("<h1 id=\"intro\" class=\"big\">Title</h1>"));
+        assert!(html.contains("<p>“Quoted” – textbold<br />\nnext</p>"));
+        assert!(html.contains("<dl>\n<dt>Term</dt>\n<dd>Meaning</dd>\n</dl>"));
+        assert!(!html.contains("<div>"));
+        assert!(html.contains("<a href=\"https://rust-lang.org\" title=\"Rust\" target=\"_blank\" rel=\"nofollow noopener\">out</a>"));
+        assert!(html.contains("<a href=\"https://example.com/a\">in</a>"));
+
+        // default config keeps raw html and plain links
+        let html = renderer
+            .render(content, &RenderOptions::default())
+            .unwrap()
+            .html;
+        assert!(html.contains("<div>raw</div>"));
+        assert!(html.contains("<a href=\"https://rust-lang.org\" title=\"Rust\">out</a>"));
+    }
+
+    #[test]
+    fn test_markdown_to_text() {
+        let text = markdown_to_text("# Title\n\nSome **bold** [link](http://a.com) `code`<br>\n");
+        assert_eq!(
+            text.split_whitespace().collect::<Vec<_>>().join(" "),
+            "Title Some bold link code"
+        );
+    }
+}
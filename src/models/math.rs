@@ -0,0 +1,164 @@
+use crate::models;
+
+#[derive(Debug, PartialEq)]
+pub struct MathSpan {
+    pub range: std::ops::Range<usize>,
+    pub display: bool,
+}
+
+impl MathSpan {
+    // latex text without $ delimiters
+    pub fn latex<'a>(&self, content: &'a str) -> &'a str {
+        let delimiter = if self.display { 2 } else { 1 };
+        &content[self.range.start + delimiter..self.range.end - delimiter]
+    }
+}
+
+// find closing $ of inline math, it is not after whitespace and not before digit, like pandoc
+fn find_inline_end(
+    content: &str,
+    start: usize,
+    code_ranges: &[std::ops::Range<usize>],
+) -> Option<usize> {
+    let bytes = content.as_bytes();
+    let mut i = start;
+    while i < bytes.len() {
+        if code_ranges.iter().any(|r| r.contains(&i)) {
+            return None;
+        }
+        match bytes[i] {
+            b'\\' => i += 1,
+            b'\n' if bytes.get(i + 1) == Some(&b'\n') => return None,
+            b'$' if !bytes[i - 1].is_ascii_whitespace()
+                && !bytes.get(i + 1).is_some_and(|c| c.is_ascii_digit()) =>
+            {
+                return Some(i)
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    None
+}
+
+// find closing $$ of display math, math does not go across code
+fn find_display_end(
+    content: &str,
+    start: usize,
+    code_ranges: &[std::ops::Range<usize>],
+) -> Option<usize> {
+    let bytes = content.as_bytes();
+    let mut i = start;
+    while i < bytes.len() {
+        if code_ranges.iter().any(|r| r.contains(&i)) {
+            return None;
+        }
+        match bytes[i] {
+            b'\\' => i += 1,
+            b'$' if bytes.get(i + 1) == Some(&b'$') => return Some(i),
+            _ => {}
+        }
+        i += 1;
+    }
+    None
+}
+
+// parse $...$ and $$...$$ outside of code
+pub fn parse_math_spans(content: &str) -> Vec<MathSpan> {
+    let code_ranges = models::markdown::parse_code_ranges(content);
+    let bytes = content.as_bytes();
+    let mut spans = vec![];
+    let mut i = 0;
+    while i < bytes.len() {
+        if let Some(code) = code_ranges.iter().find(|r| r.contains(&i)) {
+            i = code.end;
+            continue;
+        }
+        match bytes[i] {
+            b'\\' => i += 1,
+            b'$' if bytes.get(i + 1) == Some(&b'$') => {
+                if let Some(end) = find_display_end(content, i + 2, &code_ranges) {
+                    let end = end + 2;
+                    spans.push(MathSpan {
+                        range: i..end,
+                        display: true,
+                    });
+                    i = end;
+                    continue;
+                }
+                i += 1;
+            }
+            b'$' if bytes.get(i + 1).is_some_and(|c| !c.is_ascii_whitespace()) => {
+                if let Some(end) = find_inline_end(content, i + 1, &code_ranges) {
+                    spans.push(MathSpan {
+                        range: i..end + 1,
+                        display: false,
+                    });
+                    i = end + 1;
+                    continue;
+                }
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    spans
+}
+
+pub fn latex_to_mathml(latex: &str, display: bool) -> Result<String, String> {
+    let style = if display {
+        latex2mathml::DisplayStyle::Block
+    } else {
+        latex2mathml::DisplayStyle::Inline
+    };
+    let mathml = latex2mathml::latex_to_mathml(latex.trim(), style)
+        .map_err(|e| format!("math is not valid: {}, {}", latex.trim(), e))?;
+    // some errors are written into mathml instead of returned
+    if let Some(pos) = mathml.find("[PARSE ERROR:") {
+        let error = &mathml[pos + 1..];
+        let error = &error[..error.find(']').unwrap_or(error.len())];
+        return Err(format!("math is not valid: {}, {}", latex.trim(), error));
+    }
+    Ok(mathml)
+}
+
+#[cfg(test)]
+mod math_tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_math_spans() {
+        let content =
+            "Euler $e^{i\\pi} + 1 = 0$ costs $5 and $10 today `$code$`\n\n$$\n\\sum_{i=1}^n i\n$$";
+        let spans = parse_math_spans(content);
+        assert_eq!(spans.len(), 2);
+        assert_eq!(spans[0].latex(content), "e^{i\\pi} + 1 = 0");
+        assert!(!spans[0].display);
+        assert_eq!(spans[1].latex(content), "\n\\sum_{i=1}^n i\n");
+        assert!(spans[1].display);
+
+        // inline math is not across paragraphs
+        assert!(parse_math_spans("$a\n\nb$").is_empty());
+        assert!(parse_math_spans("$ a $").is_empty());
+
+        // closing $$ in code is not end of display math
+        assert!(parse_math_spans("$$a `$$` b$$").is_empty());
+        let content = "Cost $$ in `$$` code\n\n$$x$$";
+        let spans = parse_math_spans(content);
+        assert_eq!(spans.len(), 1);
+        assert_eq!(spans[0].latex(content), "x");
+        assert!(spans[0].display);
+    }
+
+    #[test]
+    fn test_latex_to_mathml() {
+        let html = latex_to_mathml("x^2", false).unwrap();
+        assert!(html.starts_with("<math"));
+        assert!(html.contains("<msup>"));
+        assert!(latex_to_mathml("x^2", true)
+            .unwrap()
+            .contains("display=\"block\""));
+        assert!(latex_to_mathml("\\frac{1}", false).is_err());
+        assert!(latex_to_mathml("{", false).is_err());
+    }
+}
@@ -4,11 +4,27 @@ pub use author::Author;
 mod config;
 pub use config::Config;
 pub use config::DirectoryConfig;
+pub use config::ExcerptConfig;
+pub use config::HighlightConfig;
+pub use config::LanguageConfig;
+pub use config::MarkdownConfig;
+pub use config::MarkdownOverrides;
+pub use config::SlugConfig;
 pub use config::UrlConfig;
 
 mod post;
+pub use post::FrontMatterFormat;
 pub use post::Post;
+#[cfg(test)]
 pub use post::PostMetadata;
+pub use post::PostNeighbours;
+pub use post::TimeParser;
+
+mod timezone;
+pub use timezone::Timezone;
+
+mod i18n;
+pub use i18n::I18n;
 
 mod theme;
 pub use theme::Theme;
@@ -16,22 +32,61 @@ pub use theme::ThemeEmbedAssets;
 
 mod site;
 pub use site::Site;
+pub use site::SiteOptions;
 
 mod tag;
 pub use tag::Tag;
 
+mod language;
+pub use language::Language;
+pub use language::Translation;
+
+mod category;
+pub use category::Category;
+
+mod related;
+
+mod slug;
+
+mod taxonomy;
+pub use taxonomy::Taxonomy;
+pub use taxonomy::TaxonomyConfig;
+
 mod tplvars;
+pub use tplvars::ArchiveVars;
 pub use tplvars::GlobalVars;
 pub use tplvars::PaginationVars;
+pub use tplvars::PostVars;
 pub use tplvars::TemplateVars;
-pub use tplvars::ArchiveVars;
+
+mod highlight;
+pub use highlight::Highlighter;
+
+mod links;
+pub use links::ContentLinks;
+
+mod markdown;
+pub use markdown::markdown_to_text;
+pub use markdown::MarkdownRenderer;
+pub use markdown::RenderOptions;
+pub use markdown::TocItem;
+
+mod excerpt;
+pub use excerpt::build_excerpt;
+
+mod shortcode;
+
+mod math;
+
+mod admonition;
+
+mod deflist;
 
 mod output;
 pub use output::Output;
 
 mod pagination;
 pub use pagination::Pagination;
-pub use pagination::PaginationItem;
 
 mod archive;
 pub use archive::Archive;
@@ -69,6 +69,7 @@ impl PaginationItem {
             next: self.next,
             total: self.total,
             total_pages: self.total_pages,
+            size: self.size,
             has_prev: self.has_previous,
             has_next: self.has_next,
             current_url: self.current_url(),
@@ -112,8 +113,9 @@ mod tests {
         assert_eq!(page.total, 99);
         assert_eq!(page.total_pages, 10);
         assert_eq!(page.size, 10);
-        assert_eq!(page.has_previous, true);
-        assert_eq!(page.has_next, true);
+        assert_eq!(page.build_template_vars().size, 10);
+        assert!(page.has_previous);
+        assert!(page.has_next);
         assert_eq!(page.previous, 2);
         assert_eq!(page.next, 4);
         assert_eq!(page.start, 20);
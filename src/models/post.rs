@@ -1,40 +1,301 @@
 use crate::models;
-use chrono::Datelike;
+use crate::utils;
+use chrono::{Datelike, Timelike};
 use log::debug;
 
+// split trailing offset like Z, +08:00 or +0800 after time part
+fn split_offset(time_text: &str) -> (&str, Option<chrono::FixedOffset>) {
+    let tail = match time_text.get(10..) {
+        Some(tail) if !tail.is_empty() => tail,
+        _ => return (time_text, None),
+    };
+    if let Some(text) = time_text.strip_suffix(['Z', 'z']) {
+        return (text, chrono::FixedOffset::east_opt(0));
+    }
+    if let Some(pos) = tail.rfind(['+', '-']) {
+        let pos = pos + 10;
+        if let Some(offset) = models::timezone::parse_offset(&time_text[pos..]) {
+            return (time_text[..pos].trim_end(), Some(offset));
+        }
+    }
+    (time_text, None)
+}
+
+// builtin formats, tried after offset is split, T separator is replaced by space and / by -
+const TIME_FORMATS: [&str; 2] = ["%Y-%m-%d %H:%M:%S%.f", "%Y-%m-%d %H:%M"];
+
+// parse time in extra formats first, then builtin ones like 2020-01-02 03:04:05, 2020/01/02,
+// or rfc3339 like 2020-01-02T03:04:05+08:00. offset is returned if time has it
+pub fn parse_time_offset(
+    time_str: &str,
+    formats: &[String],
+) -> Result<(chrono::NaiveDateTime, Option<chrono::FixedOffset>), Box<dyn std::error::Error>> {
+    let time_text = time_str.trim();
+    for format in formats {
+        if let Ok(datetime) = chrono::DateTime::parse_from_str(time_text, format) {
+            return Ok((datetime.naive_local(), Some(*datetime.offset())));
+        }
+        if let Ok(datetime) = chrono::NaiveDateTime::parse_from_str(time_text, format) {
+            return Ok((datetime, None));
+        }
+        if let Ok(date) = chrono::NaiveDate::parse_from_str(time_text, format) {
+            return Ok((date.and_hms_opt(0, 0, 0).unwrap(), None));
+        }
+    }
+    let (time_text, offset) = split_offset(time_text);
+    let time_text = time_text.replacen(['T', 't'], " ", 1).replace('/', "-");
+    for format in TIME_FORMATS {
+        if let Ok(datetime) = chrono::NaiveDateTime::parse_from_str(&time_text, format) {
+            return Ok((datetime, offset));
+        }
+    }
+    if let Ok(date) = chrono::NaiveDate::parse_from_str(&time_text, "%Y-%m-%d") {
+        return Ok((date.and_hms_opt(0, 0, 0).unwrap(), offset));
+    }
+    Err(format!("time string is not valid: {}", time_str).into())
+}
+
+// wall time as written, offset is ignored, posts are parsed by TimeParser
+#[cfg(test)]
 pub fn parse_time(time_str: &str) -> Result<chrono::NaiveDateTime, Box<dyn std::error::Error>> {
-    let mut time_text = time_str.trim().to_string();
-    if time_text.len() == 10 {
-        time_text = format!("{time_str} 00:00:00");
-    } else if time_text.len() == 16 {
-        time_text = format!("{time_str}:00");
-    }
-    if time_text.len() != 19 {
-        return Err(format!("time string is not valid: {}", time_str).into());
-    }
-    Ok(chrono::NaiveDateTime::parse_from_str(
-        &time_text,
-        "%Y-%m-%d %H:%M:%S",
-    )?)
+    Ok(parse_time_offset(time_str, &[])?.0)
+}
+
+// parser of front matter times, time with offset is converted to wall time in site timezone
+#[derive(Debug, Default, Clone)]
+pub struct TimeParser {
+    pub timezone: models::Timezone,
+    // extra chrono formats tried before builtin ones
+    pub formats: Vec<String>,
+}
+
+impl TimeParser {
+    pub fn new(config: &models::Config) -> Result<TimeParser, Box<dyn std::error::Error>> {
+        Ok(TimeParser {
+            timezone: config.get_timezone()?,
+            formats: config.site.date_formats.clone(),
+        })
+    }
+
+    pub fn parse(
+        &self,
+        time_str: &str,
+    ) -> Result<chrono::NaiveDateTime, Box<dyn std::error::Error>> {
+        let (datetime, offset) = parse_time_offset(time_str, &self.formats)?;
+        Ok(match offset {
+            Some(offset) => self
+                .timezone
+                .wall_time(&datetime.and_local_timezone(offset).unwrap()),
+            None => datetime,
+        })
+    }
+}
+
+// time is string, or native toml datetime
+fn deserialize_time<'de, D: serde::Deserializer<'de>>(deserializer: D) -> Result<String, D::Error> {
+    let mut value = <serde_json::Value as serde::Deserialize>::deserialize(deserializer)?;
+    normalize_extra_value(&mut value);
+    match value {
+        serde_json::Value::String(s) => Ok(s),
+        v => Err(serde::de::Error::custom(format!(
+            "time string is not valid: {}",
+            v
+        ))),
+    }
+}
+
+fn deserialize_option_time<'de, D: serde::Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<String>, D::Error> {
+    deserialize_time(deserializer).map(Some)
 }
 
 #[derive(Debug, Default, serde::Serialize, serde::Deserialize)]
 pub struct PostMetadata {
     pub title: String,
+    // built from title or file name if it is empty
+    #[serde(default)]
     pub slug: String,
+    #[serde(deserialize_with = "deserialize_time")]
     pub date: String,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(
+        default,
+        deserialize_with = "deserialize_option_time",
+        skip_serializing_if = "Option::is_none"
+    )]
     pub updated: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub tags: Option<Vec<String>>,
     #[serde(skip_serializing_if = "Option::is_none")]
+    pub categories: Option<Vec<String>>,
+    // permalink format of this post, it overrides the format in config
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub permalink: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub template: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub language: Option<String>,
+    // posts with same key are translations of each other
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub translation_key: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub comments: Option<bool>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub author: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub draft: Option<bool>,
+    #[serde(
+        default,
+        deserialize_with = "deserialize_option_time",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub expires: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub toc: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub summary: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub math: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub markdown: Option<models::MarkdownOverrides>,
+    #[serde(flatten)]
+    pub extra: std::collections::HashMap<String, serde_json::Value>,
+}
+
+// toml datetime is deserialized as a private wrapped map, convert it to string
+fn normalize_extra_value(value: &mut serde_json::Value) {
+    match value {
+        serde_json::Value::Object(map) => {
+            if map.len() == 1 {
+                if let Some(serde_json::Value::String(s)) = map.get("$__toml_private_datetime") {
+                    *value = serde_json::Value::String(s.clone());
+                    return;
+                }
+            }
+            map.values_mut().for_each(normalize_extra_value);
+        }
+        serde_json::Value::Array(values) => values.iter_mut().for_each(normalize_extra_value),
+        _ => {}
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FrontMatterFormat {
+    Yaml,
+    Toml,
+    Json,
+}
+
+impl FrontMatterFormat {
+    pub fn parse(name: &str) -> Result<FrontMatterFormat, Box<dyn std::error::Error>> {
+        match name {
+            "yaml" => Ok(FrontMatterFormat::Yaml),
+            "toml" => Ok(FrontMatterFormat::Toml),
+            "json" => Ok(FrontMatterFormat::Json),
+            _ => Err(format!("front matter format is not supported: {}", name).into()),
+        }
+    }
+}
+
+// split front matter wrapped by delimiter lines, returns None if content is not started with delimiter
+fn split_delimited<'a>(
+    content: &'a str,
+    delimiter: &str,
+) -> Result<Option<(&'a str, &'a str)>, Box<dyn std::error::Error>> {
+    let rest = match content.strip_prefix(delimiter).and_then(|rest| {
+        rest.strip_prefix('\n')
+            .or_else(|| rest.strip_prefix("\r\n"))
+    }) {
+        Some(rest) => rest,
+        None => return Ok(None),
+    };
+    let mut offset = 0;
+    for line in rest.split_inclusive('\n') {
+        if line.trim_end() == delimiter {
+            return Ok(Some((&rest[..offset], &rest[offset + line.len()..])));
+        }
+        offset += line.len();
+    }
+    Err(format!("front matter is not closed by {}", delimiter).into())
+}
+
+// legacy format, metadata ends with a bare --- line, or wrapped by ```toml or ```yaml block
+fn split_legacy(content: &str) -> (FrontMatterFormat, String, String) {
+    let mut metadata_string = String::from("");
+    let mut metadata_format = FrontMatterFormat::Yaml;
+    let mut content_string = String::from("");
+    let mut metadata_section_flag = true;
+    for line in content.lines() {
+        // post begin with metadata section
+        if metadata_section_flag {
+            if line == "```toml" {
+                metadata_format = FrontMatterFormat::Toml;
+                continue;
+            }
+            if line == "```yaml" {
+                metadata_format = FrontMatterFormat::Yaml;
+                continue;
+            }
+            if line == "---" || line == "```" {
+                metadata_section_flag = false;
+                continue;
+            }
+            metadata_string.push_str(line);
+            metadata_string.push('\n');
+            continue;
+        }
+
+        // metadata is end, left lines are content
+        content_string.push_str(line);
+        content_string.push('\n');
+    }
+    (metadata_format, metadata_string, content_string)
+}
+
+fn parse_front_matter(content: &str) -> Result<(PostMetadata, String), Box<dyn std::error::Error>> {
+    // json front matter is an object at the beginning
+    if content.starts_with('{') {
+        let mut stream = serde_json::Deserializer::from_str(content).into_iter::<PostMetadata>();
+        let meta = stream.next().ok_or("front matter is empty")??;
+        return Ok((meta, content[stream.byte_offset()..].to_string()));
+    }
+    let (format, metadata, body) = if let Some((metadata, body)) = split_delimited(content, "---")?
+    {
+        (
+            FrontMatterFormat::Yaml,
+            metadata.to_string(),
+            body.to_string(),
+        )
+    } else if let Some((metadata, body)) = split_delimited(content, "+++")? {
+        (
+            FrontMatterFormat::Toml,
+            metadata.to_string(),
+            body.to_string(),
+        )
+    } else {
+        split_legacy(content)
+    };
+    let meta = match format {
+        FrontMatterFormat::Toml => toml::from_str(&metadata)?,
+        _ => serde_yaml::from_str(&metadata)?,
+    };
+    Ok((meta, body))
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct PostNeighbours {
+    pub prev: Option<usize>,
+    pub next: Option<usize>,
+}
+
+impl PostNeighbours {
+    // posts_index is sorted by date desc, so prev is the older one and next is the newer one
+    pub fn new(posts_index: &[usize], pos: usize) -> PostNeighbours {
+        PostNeighbours {
+            prev: posts_index.get(pos + 1).copied(),
+            next: pos.checked_sub(1).map(|p| posts_index[p]),
+        }
+    }
 }
 
 #[derive(Debug, Default)]
@@ -43,13 +304,27 @@ pub struct Post {
     pub brief_markdown: String,
     pub content_markdown: String,
     pub brief_html: String,
+    // brief is not set by summary or more marker, it should be built by excerpt config
+    pub brief_auto: bool,
     pub content_html: String,
+    pub toc: Vec<models::TocItem>,
+    pub word_count: usize,
+    pub reading_minutes: usize,
     pub datetime: Option<chrono::NaiveDateTime>,
     pub updated_datetime: Option<chrono::NaiveDateTime>,
+    pub expires_datetime: Option<chrono::NaiveDateTime>,
     pub author: Option<models::Author>,
 
     pub local_file: String,
+    // lines before content in local file, front matter and blank lines
+    pub content_line_offset: usize,
+    // directory of page bundle, the post is its index.md
+    pub bundle_dir: Option<String>,
     pub slug_url: String,
+    pub neighbours: PostNeighbours,
+    pub tag_neighbours: Vec<(String, PostNeighbours)>,
+    pub related_index: Vec<usize>,
+    pub translations: Vec<models::Translation>,
 }
 
 impl Post {
@@ -63,6 +338,18 @@ impl Post {
         }
     }
 
+    pub fn is_draft(&self) -> bool {
+        self.meta.draft.unwrap_or(false)
+    }
+
+    pub fn is_future(&self, now: &chrono::NaiveDateTime) -> bool {
+        self.datetime.is_some_and(|dt| dt > *now)
+    }
+
+    pub fn is_expired(&self, now: &chrono::NaiveDateTime) -> bool {
+        self.expires_datetime.is_some_and(|dt| dt <= *now)
+    }
+
     pub fn parse_meta(&mut self) -> Result<(), Box<dyn std::error::Error>> {
         // fill default values
         if self.meta.slug.starts_with('/') {
@@ -81,11 +368,15 @@ impl Post {
             self.meta.tags = Some(vec![]); // fill empty slice to make sure other functions working
         }
 
-        // parse time
-        self.datetime = Some(parse_time(self.meta.date.as_str())?);
-        self.updated_datetime = Some(parse_time(self.meta.updated.as_ref().unwrap().as_str())?);
+        for value in self.meta.extra.values_mut() {
+            normalize_extra_value(value);
+        }
 
-        // parse brief
+        // parse brief, summary in front matter takes priority
+        if let Some(summary) = &self.meta.summary {
+            self.brief_markdown = summary.trim().to_string();
+            return Ok(());
+        }
         let mut seperator_index = self.content_markdown.find("<!-- more -->").unwrap_or(0);
         if seperator_index < 1 {
             seperator_index = self.content_markdown.find("<!--more-->").unwrap_or(0);
@@ -99,47 +390,43 @@ impl Post {
                 .to_string();
         } else {
             self.brief_markdown = self.content_markdown.clone();
+            self.brief_auto = true;
         }
         Ok(())
     }
 
-    pub fn from_str(content: &str) -> Result<Post, Box<dyn std::error::Error>> {
-        let mut metadata_string = String::from("");
-        let mut metadata_format = "yaml";
-        let mut content_string = String::from("");
-        let mut metadata_section_flag = true;
-        let mut content_lines = content.trim().lines();
-        for line in content_lines.by_ref() {
-            // post begin with metadata section
-            if metadata_section_flag {
-                if line == "```toml" {
-                    metadata_format = "toml";
-                    continue;
-                }
-                if line == "```yaml" {
-                    metadata_format = "yaml";
-                    continue;
-                }
-                if line == "---" || line == "```" {
-                    metadata_section_flag = false;
-                    continue;
-                }
-                metadata_string.push_str(line);
-                metadata_string.push('\n');
-                continue;
-            }
+    // parse times with site config, times with offset are converted to site timezone,
+    // it is the only place to set datetimes so invalid times are always reported
+    pub fn parse_times(&mut self, parser: &TimeParser) -> Result<(), Box<dyn std::error::Error>> {
+        let parse = |name: &str, value: &str| {
+            parser
+                .parse(value)
+                .map_err(|_| format!("{} is not valid: {:?}", name, value))
+        };
+        self.datetime = Some(parse("date", &self.meta.date)?);
+        if let Some(updated) = &self.meta.updated {
+            self.updated_datetime = Some(parse("updated", updated)?);
+        }
+        if let Some(expires) = &self.meta.expires {
+            self.expires_datetime = Some(parse("expires", expires)?);
+        }
+        Ok(())
+    }
 
-            // metadata is end, left lines are content
-            content_string.push_str(line);
-            content_string.push('\n');
+    pub fn build_brief(&mut self, config: &models::ExcerptConfig) {
+        if self.brief_auto {
+            self.brief_markdown = models::build_excerpt(&self.content_markdown, config);
         }
+    }
+
+    pub fn from_str(content: &str) -> Result<Post, Box<dyn std::error::Error>> {
+        let (meta, content_string) = parse_front_matter(content.trim())?;
         let mut post = Post::default();
-        if metadata_format == "yaml" {
-            post.meta = serde_yaml::from_str(metadata_string.as_str())?;
-        } else if metadata_format == "toml" {
-            post.meta = toml::from_str(metadata_string.as_str())?;
-        }
+        post.meta = meta;
         post.content_markdown = content_string.trim().to_string();
+        if let Some(pos) = content.rfind(&post.content_markdown) {
+            post.content_line_offset = content[..pos].matches('\n').count();
+        }
         post.parse_meta()?;
         Ok(post)
     }
@@ -151,46 +438,145 @@ impl Post {
         Ok(post)
     }
 
-    pub fn list_from_dir(dir_path: &str) -> Result<Vec<Post>, Box<dyn std::error::Error>> {
+    pub fn list_from_dir(
+        dir_path: &str,
+        parser: &TimeParser,
+    ) -> Result<Vec<Post>, Box<dyn std::error::Error>> {
+        // directory with index.md is a page bundle, other files in it are resources
+        let bundle_dirs: Vec<std::path::PathBuf> = walkdir::WalkDir::new(dir_path)
+            .min_depth(1)
+            .into_iter()
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.file_type().is_dir() && entry.path().join("index.md").is_file())
+            .map(|entry| entry.into_path())
+            .collect();
+
         let mut posts = Vec::new();
         for entry in walkdir::WalkDir::new(dir_path) {
             let entry = entry.unwrap();
             let post_file_path = entry.path();
             let post_file_path_str = post_file_path.to_str().unwrap();
             if post_file_path.is_file() && post_file_path_str.ends_with(".md") {
-                let post = match Post::from_file(post_file_path_str) {
-                    Ok(post) => post,
-                    Err(e) => {
-                        return Err(e);
-                    }
-                };
+                let bundle_dir = post_file_path.parent().filter(|dir| {
+                    post_file_path.ends_with("index.md") && bundle_dirs.contains(&dir.to_path_buf())
+                });
+                if bundle_dir.is_none()
+                    && bundle_dirs
+                        .iter()
+                        .any(|dir| post_file_path.starts_with(dir))
+                {
+                    continue;
+                }
+                let mut post = Post::from_file(post_file_path_str)
+                    .and_then(|mut post| {
+                        post.parse_times(parser)?;
+                        Ok(post)
+                    })
+                    .map_err(|e| {
+                        format!("source file is not valid: {}, {}", post_file_path_str, e)
+                    })?;
+                post.bundle_dir = bundle_dir.map(|dir| dir.to_str().unwrap().to_string());
                 posts.push(post);
                 debug!("Loaded source: {}", post_file_path_str);
             }
         }
         // sort by date
-        posts.sort_by(|a, b| b.datetime.unwrap().cmp(&a.datetime.unwrap()));
+        posts.sort_by_key(|p| std::cmp::Reverse(p.datetime.unwrap()));
         Ok(posts)
     }
 
-    pub fn to_file(&self, file_path: &str) -> Result<(), Box<dyn std::error::Error>> {
-        let mut post_string = String::from("");
-        let metadata_string = serde_yaml::to_string(&self.meta).unwrap();
-        post_string.push_str(metadata_string.as_str());
-        post_string.push_str("---\n\n");
+    pub fn to_file(
+        &self,
+        file_path: &str,
+        format: FrontMatterFormat,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let mut post_string = match format {
+            FrontMatterFormat::Yaml => {
+                format!("---\n{}---\n\n", serde_yaml::to_string(&self.meta)?)
+            }
+            FrontMatterFormat::Toml => format!("+++\n{}+++\n\n", toml::to_string(&self.meta)?),
+            FrontMatterFormat::Json => {
+                format!("{}\n\n", serde_json::to_string_pretty(&self.meta)?)
+            }
+        };
         post_string.push_str(&self.content_markdown);
         post_string.push('\n');
-        std::fs::write(file_path, post_string).unwrap();
+        std::fs::write(file_path, post_string)?;
         Ok(())
     }
 
-    pub fn set_slug_url(&mut self, permalink: &str) {
+    pub fn get_terms(&self, key: &str) -> Vec<String> {
+        let value_to_term = |v: &serde_json::Value| match v {
+            serde_json::Value::String(s) => Some(s.clone()),
+            serde_json::Value::Number(n) => Some(n.to_string()),
+            _ => None,
+        };
+        match self.meta.extra.get(key) {
+            Some(serde_json::Value::Array(values)) => {
+                values.iter().filter_map(value_to_term).collect()
+            }
+            Some(value) => value_to_term(value).into_iter().collect(),
+            None => vec![],
+        }
+    }
+
+    pub fn get_category_path(&self) -> String {
+        let path = models::category::build_category_path(
+            self.meta.categories.as_deref().unwrap_or_default(),
+        );
+        if path.is_empty() {
+            return models::category::DEFAULT_CATEGORY.to_string();
+        }
+        path
+    }
+
+    pub fn set_reading_stats(&mut self, site_config: &models::config::SiteConfig) {
+        let words = utils::count_words(&models::markdown_to_text(&self.content_markdown));
+        self.word_count = words.total();
+        self.reading_minutes =
+            words.reading_minutes(site_config.reading_speed, site_config.cjk_reading_speed);
+    }
+
+    // short hash of content, it keeps same until content is changed
+    pub fn content_hash(&self) -> String {
+        use md5::{Digest, Md5};
+        let mut hasher = Md5::new();
+        hasher.update(self.content_markdown.as_bytes());
+        let hex_hash = base16ct::lower::encode_string(&hasher.finalize());
+        hex_hash[..8].to_string()
+    }
+
+    // :title and :author are slugified, :yearday is replaced before :year
+    pub fn set_slug_url(&mut self, permalink: &str, slug_config: &models::SlugConfig) {
         let datetime = self.datetime.unwrap();
-        self.slug_url = permalink
+        let author = self.meta.author.as_deref().unwrap_or_default();
+        let mut slug_url = permalink
+            .replace(":yearday", format!("{:0>3}", datetime.ordinal()).as_str())
             .replace(":year", datetime.year().to_string().as_str())
             .replace(":month", format!("{:0>2}", datetime.month()).as_str())
             .replace(":day", format!("{:0>2}", datetime.day()).as_str())
+            .replace(":hour", format!("{:0>2}", datetime.hour()).as_str())
+            .replace(":minute", format!("{:0>2}", datetime.minute()).as_str())
+            .replace(
+                ":category",
+                models::category::build_category_slug(&self.get_category_path(), slug_config)
+                    .as_str(),
+            )
+            .replace(":filename", models::slug::file_name(self).as_str())
+            .replace(
+                ":title",
+                models::slug::slugify(&self.meta.title, slug_config).as_str(),
+            )
+            .replace(
+                ":author",
+                models::slug::slugify(author, slug_config).as_str(),
+            )
+            .replace(":lang", self.meta.language.as_deref().unwrap_or_default())
             .replace(":slug", self.meta.slug.as_str());
+        if slug_url.contains(":hash") {
+            slug_url = slug_url.replace(":hash", &self.content_hash());
+        }
+        self.slug_url = slug_url;
     }
 }
 
@@ -234,6 +620,92 @@ mod parse_time_tests {
         assert_eq!(datetime.minute(), 0);
         assert_eq!(datetime.second(), 0);
     }
+
+    #[test]
+    fn test_parse_time_offset() {
+        let parse = |time_str: &str| parse_time_offset(time_str, &[]).unwrap();
+        let (datetime, offset) = parse("2020-01-02T03:04:05+08:00");
+        assert_eq!(datetime.to_string(), "2020-01-02 03:04:05");
+        assert_eq!(offset, chrono::FixedOffset::east_opt(8 * 3600));
+        let (datetime, offset) = parse("2020-01-02T03:04:05.123Z");
+        assert_eq!(datetime.to_string(), "2020-01-02 03:04:05.123");
+        assert_eq!(offset, chrono::FixedOffset::east_opt(0));
+        let (_, offset) = parse("2020-01-02 03:04 -0500");
+        assert_eq!(offset, chrono::FixedOffset::east_opt(-5 * 3600));
+        assert_eq!(parse("2020-01-02").1, None);
+        assert_eq!(
+            parse("2020/01/02 03:04").0.to_string(),
+            "2020-01-02 03:04:00"
+        );
+        assert_eq!(parse("2020/1/2").0.to_string(), "2020-01-02 00:00:00");
+        assert!(parse_time_offset("2020-13-02", &[]).is_err());
+        assert!(parse_time_offset("yesterday", &[]).is_err());
+
+        // extra formats
+        let formats = vec!["%d.%m.%Y %H:%M".to_string(), "%B %d, %Y".to_string()];
+        let (datetime, _) = parse_time_offset("02.01.2020 03:04", &formats).unwrap();
+        assert_eq!(datetime.to_string(), "2020-01-02 03:04:00");
+        let (datetime, _) = parse_time_offset("January 02, 2020", &formats).unwrap();
+        assert_eq!(datetime.to_string(), "2020-01-02 00:00:00");
+    }
+
+    #[test]
+    fn test_time_parser() {
+        // time with offset is converted to site timezone
+        let parser = TimeParser {
+            timezone: models::Timezone::parse("Asia/Shanghai").unwrap(),
+            formats: vec!["%d/%m/%Y %H:%M %z".to_string()],
+        };
+        let datetime = parser.parse("2020-01-02T03:04:05Z").unwrap();
+        assert_eq!(datetime.to_string(), "2020-01-02 11:04:05");
+        let datetime = parser.parse("2020-01-02 03:04:05").unwrap();
+        assert_eq!(datetime.to_string(), "2020-01-02 03:04:05");
+        let datetime = parser.parse("02/01/2020 03:04 +0000").unwrap();
+        assert_eq!(datetime.to_string(), "2020-01-02 11:04:00");
+    }
+
+    #[test]
+    fn test_parse_native_times() {
+        // native toml datetime
+        let content = "+++\ntitle = \"a\"\nslug = \"a\"\ndate = 2020-01-02T03:04:05+08:00\nexpires = 2020-02-01\n+++\ncontent";
+        let mut post = Post::from_str(content).unwrap();
+        assert_eq!(post.meta.date, "2020-01-02T03:04:05+08:00");
+        post.parse_times(&TimeParser::default()).unwrap();
+        assert!(post.expires_datetime.is_some());
+
+        // custom format is parsed by parser, error names the value
+        let content = "---\ntitle: a\nslug: a\ndate: 02.01.2020\n---\ncontent";
+        let mut post = Post::from_str(content).unwrap();
+        let err = post.parse_times(&TimeParser::default()).unwrap_err();
+        assert_eq!(err.to_string(), "date is not valid: \"02.01.2020\"");
+        let parser = TimeParser {
+            formats: vec!["%d.%m.%Y".to_string()],
+            ..TimeParser::default()
+        };
+        post.parse_times(&parser).unwrap();
+        assert_eq!(post.datetime.unwrap().to_string(), "2020-01-02 00:00:00");
+    }
+}
+
+#[cfg(test)]
+mod post_neighbours_tests {
+    use super::*;
+
+    #[test]
+    fn test_neighbours() {
+        let posts_index = vec![1, 3, 4];
+        let first = PostNeighbours::new(&posts_index, 0);
+        assert_eq!(first.prev, Some(3));
+        assert_eq!(first.next, None);
+
+        let middle = PostNeighbours::new(&posts_index, 1);
+        assert_eq!(middle.prev, Some(4));
+        assert_eq!(middle.next, Some(1));
+
+        let last = PostNeighbours::new(&posts_index, 2);
+        assert_eq!(last.prev, None);
+        assert_eq!(last.next, Some(3));
+    }
 }
 
 #[cfg(test)]
@@ -284,11 +756,13 @@ Workers Sites leverages the power of Workers and Workers KV by allowing develope
         assert_eq!(post.meta.comments, Some(true));
         assert_eq!(post.meta.author, Some("admin".to_string()));
         assert_eq!(post.content_markdown.len(), 423);
+        assert_eq!(post.content_line_offset, 10);
 
         // brief should be parsed if seperator is found
         assert_eq!(post.brief_markdown.len(), 155);
 
         // datetime is parsed
+        post.parse_times(&TimeParser::default()).unwrap();
         assert!(post.datetime.unwrap().year() == 2022);
         assert!(post.datetime.unwrap().month() == 5);
         assert!(post.updated_datetime.unwrap().hour() == 15);
@@ -296,15 +770,38 @@ Workers Sites leverages the power of Workers and Workers KV by allowing develope
         assert!(post.updated_datetime.unwrap().second() == 25);
 
         // slug format
-        post.set_slug_url("aaa/:year/:month/:day/:slug");
+        let slug_config = models::SlugConfig::default();
+        post.set_slug_url("aaa/:year/:month/:day/:slug", &slug_config);
         assert_eq!(post.slug_url, "aaa/2022/05/25/blog-cf-worker");
+
+        post.set_slug_url("/:category/:slug", &slug_config);
+        assert_eq!(post.slug_url, "/uncategorized/blog-cf-worker");
+        post.meta.categories = Some(vec!["Cloud".to_string(), "Workers".to_string()]);
+        post.set_slug_url("/:category/:slug", &slug_config);
+        assert_eq!(post.slug_url, "/cloud/workers/blog-cf-worker");
+
+        post.meta.author = Some("Jane Doe".to_string());
+        post.meta.language = Some("en".to_string());
+        post.local_file = "posts/cf-worker.md".to_string();
+        post.set_slug_url(
+            "/:lang/:author/:year/:yearday/:hour:minute/:filename/:title",
+            &slug_config,
+        );
+        assert_eq!(
+            post.slug_url,
+            "/en/jane-doe/2022/145/1555/cf-worker/deploy-blog-in-cloudflare-workers"
+        );
+        post.set_slug_url("/p/:hash", &slug_config);
+        assert_eq!(post.slug_url.len(), "/p/".len() + 8);
+        assert_eq!(post.slug_url, format!("/p/{}", post.content_hash()));
     }
 
     #[test]
     fn test_parse_post_file() {
         // use toml format to test
-        std::fs::write("test_post.md", &create_test_post_content_toml()).unwrap();
-        let post = Post::from_file("test_post.md").unwrap();
+        std::fs::write("test_post.md", create_test_post_content_toml()).unwrap();
+        let mut post = Post::from_file("test_post.md").unwrap();
+        post.parse_times(&TimeParser::default()).unwrap();
         assert_eq!(post.meta.title, "Deploy blog in Cloudflare Workers 2");
         assert_eq!(post.meta.date, "2022-06-26 18:30:30");
         assert_eq!(post.content_markdown.len(), 421);
@@ -315,20 +812,153 @@ Workers Sites leverages the power of Workers and Workers KV by allowing develope
         std::fs::remove_file("test_post.md").unwrap();
     }
 
+    #[test]
+    fn test_parse_post_front_matter() {
+        let contents = [
+            "---\ntitle: Post\ndate: 2022-05-25\nslug: post\n---\n\ncontent\n---\nmore",
+            "+++\ntitle = \"Post\"\ndate = \"2022-05-25\"\nslug = \"post\"\n+++\ncontent\n---\nmore",
+            "{\n  \"title\": \"Post\",\n  \"date\": \"2022-05-25\",\n  \"slug\": \"post\"\n}\ncontent\n---\nmore",
+            "```yaml\ntitle: Post\ndate: 2022-05-25\nslug: post\n```\ncontent\n---\nmore",
+        ];
+        for content in contents {
+            let post = Post::from_str(content).unwrap();
+            assert_eq!(post.meta.title, "Post");
+            assert_eq!(post.content_markdown, "content\n---\nmore");
+        }
+        assert!(Post::from_str("---\ntitle: Post\ncontent").is_err());
+    }
+
+    #[test]
+    fn test_post_to_file() {
+        let mut post = Post::from_str(&create_test_post_content()).unwrap();
+        post.meta
+            .extra
+            .insert("cover".to_string(), serde_json::json!("/a.png"));
+        for format in ["yaml", "toml", "json"] {
+            let path = format!("test_post_{}.md", format);
+            post.to_file(&path, FrontMatterFormat::parse(format).unwrap())
+                .unwrap();
+            let saved = Post::from_file(&path).unwrap();
+            assert_eq!(saved.meta.title, post.meta.title);
+            assert_eq!(saved.meta.extra["cover"], "/a.png");
+            assert_eq!(saved.content_markdown, post.content_markdown);
+            std::fs::remove_file(&path).unwrap();
+        }
+        assert!(FrontMatterFormat::parse("xml").is_err());
+    }
+
+    #[test]
+    fn test_parse_post_draft() {
+        let content = "title: Draft post\ndate: 2022-05-25\nslug: draft\ndraft: true\n---\ncontent";
+        let post = Post::from_str(content).unwrap();
+        assert!(post.is_draft());
+
+        let content = "title: Normal post\ndate: 2022-05-25\nslug: normal\n---\ncontent";
+        let post = Post::from_str(content).unwrap();
+        assert!(!post.is_draft());
+    }
+
+    #[test]
+    fn test_parse_post_schedule() {
+        let content = "title: Scheduled post\ndate: 2022-05-25 10:00\nexpires: 2022-06-01\nslug: scheduled\n---\ncontent";
+        let mut post = Post::from_str(content).unwrap();
+        post.parse_times(&TimeParser::default()).unwrap();
+        let before = parse_time("2022-05-24").unwrap();
+        let after = parse_time("2022-05-26").unwrap();
+        let expired = parse_time("2022-06-01").unwrap();
+        assert!(post.is_future(&before));
+        assert!(!post.is_future(&after));
+        assert!(!post.is_expired(&after));
+        assert!(post.is_expired(&expired));
+    }
+
+    #[test]
+    fn test_parse_post_extra() {
+        let content = "title: Post\ndate: 2022-05-25\nslug: post\ncover: /img/cover.png\nweight: 3\nwide: true\nlinks:\n- a\n- b\n---\ncontent";
+        let post = Post::from_str(content).unwrap();
+        assert_eq!(post.meta.extra["cover"], "/img/cover.png");
+        assert_eq!(post.meta.extra["weight"], 3);
+        assert_eq!(post.meta.extra["wide"], true);
+        assert_eq!(post.meta.extra["links"], serde_json::json!(["a", "b"]));
+        assert!(!post.meta.extra.contains_key("title"));
+
+        let content = "```toml\ntitle = \"Post\"\ndate = \"2022-05-25\"\nslug = \"post\"\nrating = 4.5\npublished = 2022-05-25T10:00:00Z\n[hero]\nlayout = \"wide\"\n```\ncontent";
+        let post = Post::from_str(content).unwrap();
+        assert_eq!(post.meta.extra["rating"], 4.5);
+        assert_eq!(post.meta.extra["published"], "2022-05-25T10:00:00Z");
+        assert_eq!(post.meta.extra["hero"]["layout"], "wide");
+    }
+
+    #[test]
+    fn test_parse_post_brief() {
+        let config = models::ExcerptConfig {
+            strategy: "paragraphs".to_string(),
+            paragraphs: 1,
+            ..Default::default()
+        };
+        let content = "title: Post\ndate: 2022-05-25\nslug: post\n---\nfirst\n\nsecond";
+        let mut post = Post::from_str(content).unwrap();
+        post.build_brief(&config);
+        assert_eq!(post.brief_markdown, "first");
+
+        // summary takes priority over more marker
+        let content = "title: Post\ndate: 2022-05-25\nslug: post\nsummary: short summary\n---\nfirst <!-- more -->\n\nsecond";
+        let mut post = Post::from_str(content).unwrap();
+        post.build_brief(&config);
+        assert_eq!(post.brief_markdown, "short summary");
+    }
+
     #[test]
     fn test_parse_post_dir() {
         std::fs::create_dir_all("test_post_dir").unwrap();
-        std::fs::write("test_post_dir/post_one_1.md", &create_test_post_content()).unwrap();
+        std::fs::write("test_post_dir/post_one_1.md", create_test_post_content()).unwrap();
         std::fs::write(
             "test_post_dir/post_one_2.md",
-            &create_test_post_content_toml(),
+            create_test_post_content_toml(),
         )
         .unwrap();
-        let posts = Post::list_from_dir("test_post_dir").unwrap();
+        let posts = Post::list_from_dir("test_post_dir", &TimeParser::default()).unwrap();
         assert_eq!(posts.len(), 2);
         assert_eq!(posts[0].meta.title, "Deploy blog in Cloudflare Workers 2");
         assert_eq!(posts[1].meta.title, "Deploy blog in Cloudflare Workers");
 
+        // invalid date is reported with file and value
+        std::fs::write(
+            "test_post_dir/post_bad_date.md",
+            "title: Bad\ndate: yesterday\nslug: bad\n---\ncontent",
+        )
+        .unwrap();
+        let err = Post::list_from_dir("test_post_dir", &TimeParser::default()).unwrap_err();
         std::fs::remove_dir_all("test_post_dir").unwrap();
+        assert_eq!(
+            err.to_string(),
+            "source file is not valid: test_post_dir/post_bad_date.md, date is not valid: \"yesterday\""
+        );
+    }
+
+    #[test]
+    fn test_parse_post_bundle() {
+        std::fs::create_dir_all("test_post_bundle/bundle/files").unwrap();
+        std::fs::write(
+            "test_post_bundle/bundle/index.md",
+            create_test_post_content(),
+        )
+        .unwrap();
+        std::fs::write("test_post_bundle/bundle/notes.md", "not a post").unwrap();
+        std::fs::write("test_post_bundle/bundle/files/a.md", "not a post").unwrap();
+        std::fs::write(
+            "test_post_bundle/single.md",
+            create_test_post_content_toml(),
+        )
+        .unwrap();
+        let posts = Post::list_from_dir("test_post_bundle", &TimeParser::default()).unwrap();
+        assert_eq!(posts.len(), 2);
+        assert_eq!(posts[0].bundle_dir, None);
+        assert_eq!(
+            posts[1].bundle_dir,
+            Some("test_post_bundle/bundle".to_string())
+        );
+
+        std::fs::remove_dir_all("test_post_bundle").unwrap();
     }
 }
@@ -0,0 +1,88 @@
+use crate::models;
+
+// score posts by shared tags, rare tags weigh more than common ones
+pub fn parse_related(
+    posts: &[models::Post],
+    tags: &[models::Tag],
+    count: usize,
+) -> Vec<Vec<usize>> {
+    let tags_map: std::collections::HashMap<&str, &models::Tag> =
+        tags.iter().map(|t| (t.name.as_str(), t)).collect();
+    let total = posts.len() as f64;
+
+    let mut related = Vec::with_capacity(posts.len());
+    for (index, p) in posts.iter().enumerate() {
+        if count == 0 {
+            related.push(vec![]);
+            continue;
+        }
+        let mut scores: std::collections::HashMap<usize, f64> = std::collections::HashMap::new();
+        for name in p.meta.tags.as_ref().unwrap() {
+            let tag = match tags_map.get(name.as_str()) {
+                Some(tag) => tag,
+                None => continue,
+            };
+            let weight = (1.0 + total / tag.posts_index.len() as f64).ln();
+            for other in &tag.posts_index {
+                if *other == index || posts[*other].is_draft() {
+                    continue;
+                }
+                *scores.entry(*other).or_insert(0.0) += weight;
+            }
+        }
+        let mut candidates: Vec<(usize, f64)> = scores.into_iter().collect();
+        // higher score first, newer post first when scores are equal
+        candidates.sort_by(|a, b| {
+            b.1.partial_cmp(&a.1)
+                .unwrap()
+                .then_with(|| posts[b.0].datetime.cmp(&posts[a.0].datetime))
+        });
+        related.push(candidates.into_iter().take(count).map(|c| c.0).collect());
+    }
+    related
+}
+
+#[cfg(test)]
+mod related_tests {
+    use super::*;
+
+    fn create_post(tags: &[&str], day: u32, draft: bool) -> models::Post {
+        models::Post {
+            meta: models::PostMetadata {
+                tags: Some(tags.iter().map(|t| t.to_string()).collect()),
+                draft: Some(draft),
+                ..models::PostMetadata::default()
+            },
+            datetime: Some(
+                chrono::NaiveDate::from_ymd_opt(2022, 1, day)
+                    .unwrap()
+                    .and_hms_opt(0, 0, 0)
+                    .unwrap(),
+            ),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_related() {
+        let posts = vec![
+            create_post(&["rust", "wasm"], 5, false),
+            create_post(&["rust"], 4, false),
+            create_post(&["wasm"], 3, false),
+            create_post(&["rust", "wasm"], 2, true),
+            create_post(&["rust"], 1, false),
+            create_post(&["go"], 1, false),
+        ];
+        let tags = models::Tag::parse(&posts, &models::UrlConfig::new());
+        let related = parse_related(&posts, &tags, 2);
+
+        // wasm is rarer than rust, draft post is never related
+        assert_eq!(related[0], vec![2, 1]);
+        // same score, newer post first
+        assert_eq!(related[1], vec![0, 4]);
+        assert!(related[5].is_empty());
+
+        let related = parse_related(&posts, &tags, 0);
+        assert!(related[0].is_empty());
+    }
+}
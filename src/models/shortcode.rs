@@ -0,0 +1,207 @@
+use crate::models;
+
+#[derive(Debug)]
+struct ShortcodeTag {
+    name: String,
+    args: serde_json::Map<String, serde_json::Value>,
+    closing: bool,
+    range: std::ops::Range<usize>,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Shortcode {
+    pub name: String,
+    pub args: serde_json::Map<String, serde_json::Value>,
+    // range of inner markdown of paired shortcode
+    pub body: Option<std::ops::Range<usize>>,
+    pub range: std::ops::Range<usize>,
+}
+
+// parse key="value" arguments, bare values are collected as positional args
+fn parse_args(text: &str) -> Result<serde_json::Map<String, serde_json::Value>, String> {
+    let mut args = serde_json::Map::new();
+    let mut positional = vec![];
+    let mut chars = text.chars().peekable();
+    loop {
+        while chars.next_if(|c| c.is_whitespace()).is_some() {}
+        if chars.peek().is_none() {
+            break;
+        }
+        let mut key = String::new();
+        while let Some(c) = chars.next_if(|c| !c.is_whitespace() && *c != '=' && *c != '"') {
+            key.push(c);
+        }
+        let value = if chars.next_if_eq(&'=').is_some() || key.is_empty() {
+            let mut value = String::new();
+            if chars.next_if_eq(&'"').is_some() {
+                loop {
+                    match chars.next() {
+                        Some('\\') => value.extend(chars.next()),
+                        Some('"') => break,
+                        Some(c) => value.push(c),
+                        None => return Err(format!("unclosed quote in shortcode: {}", text)),
+                    }
+                }
+            } else {
+                while let Some(c) = chars.next_if(|c| !c.is_whitespace()) {
+                    value.push(c);
+                }
+            }
+            value
+        } else {
+            positional.push(serde_json::Value::String(key));
+            continue;
+        };
+        if key.is_empty() {
+            positional.push(serde_json::Value::String(value));
+        } else {
+            args.insert(key, serde_json::Value::String(value));
+        }
+    }
+    if !positional.is_empty() {
+        args.insert("args".to_string(), serde_json::Value::Array(positional));
+    }
+    Ok(args)
+}
+
+fn parse_tag(text: &str, range: std::ops::Range<usize>) -> Result<ShortcodeTag, String> {
+    let text = text.trim();
+    let (closing, text) = match text.strip_prefix('/') {
+        Some(text) => (true, text.trim_start()),
+        None => (false, text),
+    };
+    let (name, args) = text.split_once(char::is_whitespace).unwrap_or((text, ""));
+    if name.is_empty() {
+        return Err("shortcode name is empty".to_string());
+    }
+    Ok(ShortcodeTag {
+        name: name.to_string(),
+        args: parse_args(args)?,
+        closing,
+        range,
+    })
+}
+
+fn parse_tags(content: &str) -> Result<Vec<ShortcodeTag>, String> {
+    let code_ranges = models::markdown::parse_code_ranges(content);
+    let mut tags = vec![];
+    let mut offset = 0;
+    let in_code = |pos: usize| code_ranges.iter().find(|r| r.contains(&pos));
+    while let Some(start) = content[offset..].find("{{<").map(|i| i + offset) {
+        if let Some(code) = in_code(start) {
+            offset = code.end;
+            continue;
+        }
+        // >}} in code does not close the tag
+        let mut search = start + 3;
+        let end = loop {
+            match content[search..].find(">}}").map(|i| i + search) {
+                Some(pos) if in_code(pos).is_some() => search = pos + 3,
+                Some(pos) => break pos + 3,
+                None => return Err(format!("shortcode is not closed: {}", &content[start..])),
+            }
+        };
+        offset = end;
+        tags.push(parse_tag(&content[start + 3..end - 3], start..end)?);
+    }
+    Ok(tags)
+}
+
+fn single_shortcode(tag: ShortcodeTag) -> Shortcode {
+    Shortcode {
+        name: tag.name,
+        args: tag.args,
+        body: None,
+        range: tag.range,
+    }
+}
+
+// parse top level shortcodes, a tag without closing tag is a single shortcode
+pub fn parse_shortcodes(content: &str) -> Result<Vec<Shortcode>, String> {
+    let mut shortcodes = vec![];
+    let mut stack: Vec<ShortcodeTag> = vec![];
+    for tag in parse_tags(content)? {
+        if !tag.closing {
+            stack.push(tag);
+            continue;
+        }
+        let pos = match stack.iter().rposition(|t| t.name == tag.name) {
+            Some(pos) => pos,
+            None => return Err(format!("closing shortcode without opening: {}", tag.name)),
+        };
+        // tags opened after the matched one are single shortcodes
+        shortcodes.extend(stack.drain(pos + 1..).map(single_shortcode));
+        let open = stack.pop().unwrap();
+        shortcodes.push(Shortcode {
+            name: open.name,
+            args: open.args,
+            body: Some(open.range.end..tag.range.start),
+            range: open.range.start..tag.range.end,
+        });
+    }
+    shortcodes.extend(stack.into_iter().map(single_shortcode));
+
+    // shortcodes in body of paired ones are expanded when rendering the body
+    shortcodes.sort_by_key(|s| s.range.start);
+    let mut last_end = 0;
+    shortcodes.retain(|s| {
+        if s.range.start < last_end {
+            return false;
+        }
+        last_end = s.range.end;
+        true
+    });
+    Ok(shortcodes)
+}
+
+#[cfg(test)]
+mod shortcode_tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_args() {
+        let args =
+            parse_args(r#"id="abc 123" width=640 "pos one" two title="say \"hi\"""#).unwrap();
+        assert_eq!(args["id"], "abc 123");
+        assert_eq!(args["width"], "640");
+        assert_eq!(args["title"], "say \"hi\"");
+        assert_eq!(args["args"], serde_json::json!(["pos one", "two"]));
+        assert!(parse_args(r#"id="abc"#).is_err());
+    }
+
+    #[test]
+    fn test_parse_shortcodes() {
+        let shortcodes = parse_shortcodes("{{< a >}} {{< b >}}x{{< /b >}}").unwrap();
+        assert_eq!(shortcodes.len(), 2);
+        assert_eq!(shortcodes[1].body, Some(19..20));
+
+        let content = "a {{< youtube id=\"x\" >}}\n\n{{< note title=\"Tip\" >}}\ninner {{< icon star >}}\n{{< /note >}}\n\n`{{< code >}}`\n\n```\n{{< block >}}\n```";
+        let shortcodes = parse_shortcodes(content).unwrap();
+        assert_eq!(shortcodes.len(), 2);
+        assert_eq!(shortcodes[0].name, "youtube");
+        assert_eq!(shortcodes[0].body, None);
+        assert_eq!(
+            &content[shortcodes[0].range.clone()],
+            "{{< youtube id=\"x\" >}}"
+        );
+        assert_eq!(shortcodes[1].name, "note");
+        assert_eq!(shortcodes[1].args["title"], "Tip");
+        assert_eq!(
+            &content[shortcodes[1].body.clone().unwrap()],
+            "\ninner {{< icon star >}}\n"
+        );
+
+        assert!(parse_shortcodes("{{< /note >}}").is_err());
+        assert!(parse_shortcodes("{{< note ").is_err());
+
+        // opener in code is skipped before looking for closer
+        assert!(parse_shortcodes("`{{<` alone").unwrap().is_empty());
+        let content = "`{{<` then {{< icon star >}}";
+        let shortcodes = parse_shortcodes(content).unwrap();
+        assert_eq!(shortcodes.len(), 1);
+        assert_eq!(shortcodes[0].name, "icon");
+        assert_eq!(&content[shortcodes[0].range.clone()], "{{< icon star >}}");
+        // closer in code does not close the tag
+        assert!(parse_shortcodes("{{< icon `>}}`").is_err());
+    }
+}
@@ -1,78 +1,257 @@
 use crate::models;
-use chrono::{Local, TimeZone, Utc};
+use chrono::Utc;
 use log::{debug, error, info};
 
+#[derive(Debug, Default, Clone, Copy)]
+pub struct SiteOptions {
+    pub drafts: bool,
+    pub future: bool,
+}
+
+impl SiteOptions {
+    pub fn is_visible(&self, p: &models::Post, timezone: &models::Timezone) -> bool {
+        self.is_visible_at(p, &timezone.now())
+    }
+
+    pub fn is_visible_at(&self, p: &models::Post, now: &chrono::NaiveDateTime) -> bool {
+        if p.is_draft() && !self.drafts {
+            return false;
+        }
+        if p.is_future(now) && !self.future {
+            return false;
+        }
+        !p.is_expired(now)
+    }
+}
+
 pub struct Site<'a> {
     pub config: models::Config,
     pub posts: Vec<models::Post>,
     pub pages: Vec<models::Post>,
     pub tags: Vec<models::Tag>,
+    pub categories: Vec<models::Category>,
+    pub taxonomies: Vec<models::Taxonomy>,
+    // languages with own listings, posts in them are not in root listings
+    pub languages: Vec<models::Language>,
     pub theme: models::Theme<'a>,
+    // post times are wall times in it
+    pub timezone: models::Timezone,
 
+    // posts in root listings
+    posts_index: Vec<usize>,
+    markdown: models::MarkdownRenderer,
     template_vars: models::TemplateVars,
 }
 
+// posts and tags listed in root or under /<lang>/
+struct Listing<'s> {
+    language: &'s str,
+    prefix: &'s str,
+    posts_index: &'s [usize],
+    tags: &'s [models::Tag],
+}
+
+// listing pages of one tag, category or term
+struct TermPages<'s> {
+    language: &'s str,
+    name: &'s str,
+    url: &'s str,
+    page_format: &'s str,
+    posts_index: &'s [usize],
+    template: &'s str,
+}
+
 impl Site<'_> {
-    pub fn load(path: &str) -> Result<Self, Box<dyn std::error::Error>> {
+    pub fn load(path: &str, options: SiteOptions) -> Result<Self, Box<dyn std::error::Error>> {
         // 1. read config
         let config = models::Config::from_file(path)?;
         debug!("Loaded config: {}", path);
 
-        // 2. load sources
-        let posts = models::Post::list_from_dir(&config.get_posts_dir())?;
+        // 2. load sources in site timezone, skip drafts, scheduled and expired ones
+        let parser = models::TimeParser::new(&config)?;
+        let timezone = parser.timezone;
+        let mut posts = models::Post::list_from_dir(&config.get_posts_dir(), &parser)?;
+        posts.retain(|p| options.is_visible(p, &timezone));
         info!("Loaded posts: {}", posts.len());
-        let pages = models::Post::list_from_dir(&config.get_pages_dir())?;
+        let mut pages = models::Post::list_from_dir(&config.get_pages_dir(), &parser)?;
+        pages.retain(|p| options.is_visible(p, &timezone));
         info!("Loaded pages: {}", pages.len());
 
         // 3. parse theme
         let theme_dir = config.get_theme_dir();
-        let theme = models::Theme::parse(&theme_dir)?;
-        info!("Loaded theme: {}", &theme_dir);
+        let theme = models::Theme::parse(&theme_dir, &config)?;
+        info!("Loaded theme: {}", &theme.dir);
+
+        // 4. prepare markdown renderer
+        let markdown = models::MarkdownRenderer::new(&config)?;
 
         let mut site = Site {
             config,
             posts,
             pages,
             tags: vec![],
+            categories: vec![],
+            taxonomies: vec![],
+            languages: vec![],
+            posts_index: vec![],
+            markdown,
             template_vars: models::TemplateVars::default(),
             theme,
+            timezone,
         };
         site.parse_source()?;
         Ok(site)
     }
 
     fn parse_source(&mut self) -> Result<(), Box<dyn std::error::Error>> {
-        // 1. parse tags
-        self.tags = models::Tag::parse(&self.posts, &self.config.url);
-        debug!("Loaded tags: {}", self.tags.len());
-
-        // 2. parse posts
-        let slug_format = self.config.get_slug_link();
-        for p in &mut self.posts {
-            p.set_slug_url(&slug_format);
+        // 1. parse languages with own listings, and tags of root listings
+        for p in self.posts.iter_mut().chain(self.pages.iter_mut()) {
+            if p.meta.language.is_none() {
+                p.meta.language = Some(self.config.site.language.clone());
+            }
             if p.meta.author.is_none() {
                 p.meta.author = Some(self.config.site.author.clone());
             }
-            if p.meta.language.is_none() {
-                p.meta.language = Some(self.config.site.language.clone());
+            if p.meta.slug.is_empty() {
+                p.meta.slug = models::slug::build_slug(p, &self.config.slug);
+            }
+        }
+        self.languages = models::Language::parse(&self.posts, &self.config);
+        debug!("Loaded languages: {}", self.languages.len());
+        let languages = &self.languages;
+        self.posts_index = (0..self.posts.len())
+            .filter(|i| !models::Language::contains(languages, &self.posts[*i]))
+            .collect();
+        self.tags = if languages.is_empty() {
+            models::Tag::parse(&self.posts, &self.config.url)
+        } else {
+            models::Tag::parse_by(
+                &self.posts,
+                |p| {
+                    if models::Language::contains(languages, p) {
+                        vec![]
+                    } else {
+                        p.meta.tags.clone().unwrap_or_default()
+                    }
+                },
+                &self.config.url.tag_link_format,
+                &self.config.url.tag_page_format,
+            )
+        };
+        debug!("Loaded tags: {}", self.tags.len());
+        self.categories = models::Category::parse(&self.posts, &self.config.url, &self.config.slug);
+        debug!("Loaded categories: {}", self.categories.len());
+        self.taxonomies = models::Taxonomy::parse(&self.posts, &self.config.taxonomies);
+        debug!("Loaded taxonomies: {}", self.taxonomies.len());
+
+        // 2. parse posts, link neighbours of posts in same listings and of each tag, and related posts
+        let mut neighbours = vec![models::PostNeighbours::default(); self.posts.len()];
+        let mut tag_neighbours = vec![vec![]; self.posts.len()];
+        let mut related_index = vec![vec![]; self.posts.len()];
+        for listing in self.listings() {
+            for (pos, index) in listing.posts_index.iter().enumerate() {
+                neighbours[*index] = models::PostNeighbours::new(listing.posts_index, pos);
+            }
+            for tag in listing.tags {
+                for (pos, index) in tag.posts_index.iter().enumerate() {
+                    let neighbours = models::PostNeighbours::new(&tag.posts_index, pos);
+                    tag_neighbours[*index].push((tag.name.clone(), neighbours));
+                }
+            }
+            let related = models::related::parse_related(
+                &self.posts,
+                listing.tags,
+                self.config.site.related_posts,
+            );
+            for index in listing.posts_index {
+                related_index[*index] = related[*index].clone();
+            }
+        }
+        for (i, p) in self.posts.iter_mut().enumerate() {
+            p.neighbours = neighbours[i];
+            p.tag_neighbours = std::mem::take(&mut tag_neighbours[i]);
+            p.related_index = std::mem::take(&mut related_index[i]);
+        }
+        // permalinks of posts and pages are set before rendering, to resolve links between them
+        let slug_format = self.config.get_slug_link();
+        let mut content_links = models::ContentLinks::new(&self.config.directory.source);
+        for p in &mut self.posts {
+            // permalink in front matter overrides the format
+            let format = match &p.meta.permalink {
+                Some(permalink) => self.config.build_root_url(permalink),
+                None => slug_format.clone(),
+            };
+            p.set_slug_url(&format, &self.config.slug);
+            content_links.add(&p.local_file, &self.config.build_root_url(&p.slug_url));
+        }
+        for p in &mut self.pages {
+            let format = p
+                .meta
+                .permalink
+                .clone()
+                .unwrap_or_else(|| self.config.url.page_link_format.clone());
+            p.set_slug_url(&format, &self.config.slug);
+            p.slug_url = self.config.build_root_url(&p.slug_url);
+            content_links.add(&p.local_file, &p.slug_url);
+        }
+        // two sources with same permalink overwrite each other
+        let mut permalinks = std::collections::HashMap::new();
+        let urls = self
+            .posts
+            .iter()
+            .map(|p| self.config.build_root_url(&p.slug_url))
+            .zip(&self.posts)
+            .chain(
+                self.pages
+                    .iter()
+                    .map(|p| p.slug_url.clone())
+                    .zip(&self.pages),
+            );
+        for (url, p) in urls {
+            if let Some(file) = permalinks.insert(url.clone(), &p.local_file) {
+                return Err(format!(
+                    "permalink is duplicated: {}, {} and {}",
+                    url, file, p.local_file
+                )
+                .into());
             }
+        }
+        let urls: Vec<String> = self
+            .posts
+            .iter()
+            .map(|p| self.config.build_root_url(&p.slug_url))
+            .collect();
+        models::language::link_translations(&mut self.posts, &urls);
+        let urls: Vec<String> = self.pages.iter().map(|p| p.slug_url.clone()).collect();
+        models::language::link_translations(&mut self.pages, &urls);
+
+        for p in &mut self.posts {
             p.author = Some(self.config.get_author(p.meta.author.as_ref().unwrap()));
-            p.brief_html = markdown_to_html(&p.brief_markdown);
-            p.content_html = markdown_to_html(&p.content_markdown);
+            p.build_brief(&self.config.excerpt);
+            let render_options = build_render_options(&self.config, p, &content_links, &self.theme);
+            let content = self.markdown.render(&p.content_markdown, &render_options)?;
+            p.brief_html = self
+                .markdown
+                .render(&p.brief_markdown, &render_options)?
+                .html;
+            p.content_html = content.html;
+            if p.meta.toc.unwrap_or(true) {
+                p.toc = content.toc;
+            }
+            p.set_reading_stats(&self.config.site);
         }
 
         // 3. parse pages
         for p in &mut self.pages {
-            p.slug_url = self.config.build_root_url(&p.meta.slug);
-            if p.meta.author.is_none() {
-                p.meta.author = Some(self.config.site.author.clone());
-            }
-            if p.meta.language.is_none() {
-                p.meta.language = Some(self.config.site.language.clone());
-            }
             // page's brief is empty
             // p.brief_html = markdown_to_html(&p.brief_markdown);
-            p.content_html = markdown_to_html(&p.content_markdown);
+            let render_options = build_render_options(&self.config, p, &content_links, &self.theme);
+            let content = self.markdown.render(&p.content_markdown, &render_options)?;
+            p.content_html = content.html;
+            if p.meta.toc.unwrap_or(true) {
+                p.toc = content.toc;
+            }
+            p.set_reading_stats(&self.config.site);
             p.author = Some(self.config.get_author(p.meta.author.as_ref().unwrap()));
 
             // use page.hbs instead of post.hbs as default post
@@ -96,39 +275,107 @@ impl Site<'_> {
         // 2. build pages
         outputs.extend(self.build_pages()?);
 
-        // 3. build tags
-        outputs.extend(self.build_tags()?);
-
-        // 4. build index and archives
-        outputs.extend(self.build_index()?);
-        outputs.extend(self.build_archives()?);
+        // 3. build categories and user-defined taxonomies
+        outputs.extend(self.build_categories()?);
+        outputs.extend(self.build_taxonomies()?);
+
+        // 4. build pagination, tags, index, archives and rss of root and each language
+        for listing in self.listings() {
+            outputs.extend(self.build_pagination(&listing)?);
+            outputs.extend(self.build_tags(&listing)?);
+            outputs.extend(self.build_index(&listing)?);
+            outputs.extend(self.build_archives(&listing)?);
+            outputs.extend(self.build_rss(&listing)?);
+        }
         outputs.extend(self.build_404_page()?);
 
-        // 5. build rss
-        outputs.extend(self.build_rss()?);
+        // 5. build highlight css
+        outputs.extend(self.build_highlight_css()?);
 
         // 5. generate files
         let generated_count = self.generate_files(&mut outputs)?;
 
-        // 6. copy static files
+        // 6. copy static files and resources of page bundles
         self.copy_assets();
+        self.copy_bundle_assets()?;
 
         debug!("Generate files: {}", generated_count);
 
         Ok(())
     }
 
+    // root listings first, then languages with own listings
+    fn listings(&self) -> Vec<Listing<'_>> {
+        let mut listings = vec![Listing {
+            language: &self.config.site.language,
+            prefix: "",
+            posts_index: &self.posts_index,
+            tags: &self.tags,
+        }];
+        listings.extend(self.languages.iter().map(|l| Listing {
+            language: &l.code,
+            prefix: &l.prefix,
+            posts_index: &l.posts_index,
+            tags: &l.tags,
+        }));
+        listings
+    }
+
+    fn to_utc(&self, datetime: &chrono::NaiveDateTime) -> chrono::DateTime<Utc> {
+        self.timezone.localize(datetime).with_timezone(&Utc)
+    }
+
+    // lastmod of listing is the date of its newest post
+    fn build_lastmod(&self, posts_index: &[usize]) -> chrono::DateTime<Utc> {
+        match posts_index.first() {
+            Some(index) => self.to_utc(&self.posts[*index].datetime.unwrap()),
+            None => Utc::now(),
+        }
+    }
+
+    // lastmod of whole site is the date of newest post, site may have no visible posts
+    fn build_site_lastmod(&self) -> chrono::DateTime<Utc> {
+        match self.posts.first() {
+            Some(p) => self.to_utc(&p.datetime.unwrap()),
+            None => Utc::now(),
+        }
+    }
+
+    // global vars in language of post or page, with hreflang links to its translations
+    fn build_content_global_vars(
+        &self,
+        p: &models::Post,
+        url: &str,
+        post_vars: &models::PostVars,
+    ) -> models::GlobalVars {
+        let language = p.meta.language.as_deref().unwrap_or_default();
+        let mut template_vars = self.template_vars.get_global_for(language);
+        template_vars.site.language = language.to_string();
+        if !post_vars.translations.is_empty() {
+            template_vars.alternates =
+                vec![self
+                    .template_vars
+                    .build_alternate(language, &p.meta.title, url)];
+            template_vars
+                .alternates
+                .extend(post_vars.translations.iter().cloned());
+        }
+        template_vars.title = p.meta.title.clone();
+        template_vars
+    }
+
     fn build_posts(&self) -> Result<Vec<models::Output>, Box<dyn std::error::Error>> {
         let mut outputs = vec![];
 
         // build each post
         for p in &self.posts {
             let output_file = self.config.build_dist_html_filepath(&p.slug_url, true);
-            let mut template_vars = self.template_vars.get_global();
-            template_vars.post = Some(self.template_vars.build_postvars(p));
-            template_vars.title = p.meta.title.clone();
+            let post_vars = self.template_vars.build_postvars(p);
+            let url = self.config.build_root_url(&p.slug_url);
+            let mut template_vars = self.build_content_global_vars(p, &url, &post_vars);
+            template_vars.post = Some(post_vars);
 
-            let dt = Utc.from_local_datetime(&p.datetime.unwrap()).unwrap();
+            let dt = self.to_utc(&p.datetime.unwrap());
             outputs.push(models::Output {
                 visit_url: self.config.build_root_url(&p.slug_url),
                 output_files: vec![output_file],
@@ -140,25 +387,33 @@ impl Site<'_> {
             });
         }
 
-        // build pagination
-        let pagination = models::Pagination::new(self.posts.len(), self.config.url.per_page_size);
+        Ok(outputs)
+    }
+
+    fn build_pagination(
+        &self,
+        listing: &Listing,
+    ) -> Result<Vec<models::Output>, Box<dyn std::error::Error>> {
+        let mut outputs = vec![];
+
+        let pagination =
+            models::Pagination::new(listing.posts_index.len(), self.config.url.per_page_size);
+        let page_format = format!("{}{}", listing.prefix, self.config.url.post_page_format);
         for i in 0..pagination.total_pages {
-            let current_page = pagination.build_each_page(i + 1, &self.config.url.post_page_format);
+            let current_page = pagination.build_each_page(i + 1, &page_format);
             let output_file = self
                 .config
                 .build_dist_html_filepath(&current_page.current_url(), true);
-            let mut template_vars = self.template_vars.get_global();
+            let mut template_vars = self.template_vars.get_global_for(listing.language);
             template_vars.pagination = Some(current_page.build_template_vars());
-            let posts = &self.posts[current_page.start..current_page.end];
+            let posts_index = &listing.posts_index[current_page.start..current_page.end];
             let mut posts_vars = Vec::new();
-            for p in posts {
-                let post_vars = self.template_vars.build_postvars(p);
+            for index in posts_index {
+                let post_vars = self.template_vars.build_postvars(&self.posts[*index]);
                 posts_vars.push(post_vars);
             }
             template_vars.posts = Some(posts_vars);
-            let dt = Utc
-                .from_local_datetime(&posts[0].datetime.unwrap())
-                .unwrap();
+            let dt = self.build_lastmod(posts_index);
             outputs.push(models::Output {
                 visit_url: self.config.build_root_url(&current_page.current_url()),
                 output_files: vec![output_file],
@@ -172,79 +427,167 @@ impl Site<'_> {
         Ok(outputs)
     }
 
-    fn build_tags(&self) -> Result<Vec<models::Output>, Box<dyn std::error::Error>> {
+    // paginated listing pages of tag, category or term, first page is also written to its url
+    fn build_term_pages<F>(&self, term: &TermPages, set_current: F) -> Vec<models::Output>
+    where
+        F: Fn(&mut models::GlobalVars),
+    {
         let mut outputs = vec![];
+        let pagination =
+            models::Pagination::new(term.posts_index.len(), self.config.url.per_page_size);
+        for i in 0..pagination.total_pages {
+            let current_page = pagination.build_each_page(i + 1, term.page_format);
+            let output_file = self
+                .config
+                .build_dist_html_filepath(&current_page.current_url(), true);
 
-        for tag in &self.tags {
-            let pagination =
-                models::Pagination::new(tag.posts_index.len(), self.config.url.per_page_size);
-            for i in 0..pagination.total_pages {
-                let current_page = pagination.build_each_page(i + 1, &tag.page_format);
-                let output_file = self
-                    .config
-                    .build_dist_html_filepath(&current_page.current_url(), true);
-
-                // create template vars
-                let mut template_vars = self.template_vars.get_global();
-                template_vars.pagination = Some(current_page.build_template_vars());
-                template_vars.current_tag = self.template_vars.get_tag(&tag.name);
-                template_vars.title = tag.name.clone();
-
-                // set post vars list
-                let mut posts_vars = Vec::new();
-                for index in &tag.posts_index {
-                    let post_vars = self.template_vars.build_postvars(&self.posts[*index]);
-                    posts_vars.push(post_vars);
-                }
-                template_vars.posts = Some(posts_vars);
-
-                let dt = Utc
-                    .from_local_datetime(&self.posts[tag.posts_index[0]].datetime.unwrap())
-                    .unwrap();
-                let mut output = models::Output {
-                    visit_url: self.config.build_root_url(&current_page.current_url()),
-                    output_files: vec![output_file],
-                    template_vars,
-                    template_file: String::from("posts.hbs"),
-                    file_content: "".to_string(),
-                    lastmod: dt,
-                    sitemap_priority: 0.7,
+            // create template vars
+            let mut template_vars = self.template_vars.get_global_for(term.language);
+            template_vars.pagination = Some(current_page.build_template_vars());
+            set_current(&mut template_vars);
+            template_vars.title = term.name.to_string();
+
+            // set post vars list of current page
+            let posts_vars = term.posts_index[current_page.start..current_page.end]
+                .iter()
+                .map(|index| self.template_vars.build_postvars(&self.posts[*index]))
+                .collect();
+            template_vars.posts = Some(posts_vars);
+
+            let mut output = models::Output {
+                visit_url: self.config.build_root_url(&current_page.current_url()),
+                output_files: vec![output_file],
+                template_vars,
+                template_file: term.template.to_string(),
+                file_content: "".to_string(),
+                lastmod: self.build_lastmod(term.posts_index),
+                sitemap_priority: 0.7,
+            };
+            if i == 0 {
+                let index_output_file = self.config.build_dist_html_filepath(term.url, true);
+                output.output_files.push(index_output_file);
+            }
+            outputs.push(output);
+        }
+        outputs
+    }
+
+    fn build_tags(
+        &self,
+        listing: &Listing,
+    ) -> Result<Vec<models::Output>, Box<dyn std::error::Error>> {
+        let mut outputs = vec![];
+        for tag in listing.tags {
+            let term = TermPages {
+                language: listing.language,
+                name: &tag.name,
+                url: &tag.url,
+                page_format: &tag.page_format,
+                posts_index: &tag.posts_index,
+                template: "posts.hbs",
+            };
+            outputs.extend(self.build_term_pages(&term, |template_vars| {
+                template_vars.current_tag = self
+                    .template_vars
+                    .get_language_tag(listing.language, &tag.name);
+            }));
+        }
+        Ok(outputs)
+    }
+
+    fn build_categories(&self) -> Result<Vec<models::Output>, Box<dyn std::error::Error>> {
+        let mut outputs = vec![];
+        for category in &self.categories {
+            let term = TermPages {
+                language: &self.config.site.language,
+                name: &category.name,
+                url: &category.url,
+                page_format: &category.page_format,
+                posts_index: &category.posts_index,
+                template: "posts.hbs",
+            };
+            outputs.extend(self.build_term_pages(&term, |template_vars| {
+                template_vars.current_category = self.template_vars.get_category(&category.path);
+            }));
+        }
+        Ok(outputs)
+    }
+
+    fn build_taxonomies(&self) -> Result<Vec<models::Output>, Box<dyn std::error::Error>> {
+        let mut outputs = vec![];
+
+        for taxonomy in &self.taxonomies {
+            for term in &taxonomy.terms {
+                let pages = TermPages {
+                    language: &self.config.site.language,
+                    name: &term.name,
+                    url: &term.url,
+                    page_format: &term.page_format,
+                    posts_index: &term.posts_index,
+                    template: &taxonomy.template,
                 };
-                if i == 0 {
-                    let tag_index_output_file =
-                        self.config.build_dist_html_filepath(&tag.url, true);
-                    output.output_files.push(tag_index_output_file);
+                outputs.extend(self.build_term_pages(&pages, |template_vars| {
+                    template_vars.current_taxonomy = Some(taxonomy.name.clone());
+                    template_vars.current_term =
+                        self.template_vars.get_term(&taxonomy.name, &term.name);
+                }));
+
+                // each term has its own feed if enabled
+                if taxonomy.feed {
+                    let title = format!("{} - {}", term.name, self.config.site.title);
+                    let feed_url = crate::utils::merge_url(&term.url, "atom.xml");
+                    outputs.push(self.build_feed(
+                        &title,
+                        &self.config.site.description,
+                        &term.url,
+                        &feed_url,
+                        &term.posts_index,
+                    ));
                 }
-
-                outputs.push(output);
             }
         }
         Ok(outputs)
     }
 
-    fn build_index(&self) -> Result<Vec<models::Output>, Box<dyn std::error::Error>> {
+    fn build_index(
+        &self,
+        listing: &Listing,
+    ) -> Result<Vec<models::Output>, Box<dyn std::error::Error>> {
         // index page is same as first page of posts
-        let pagination = models::Pagination::new(self.posts.len(), self.config.url.per_page_size);
-        let current_page = pagination.build_each_page(1, &self.config.url.post_page_format);
+        let pagination =
+            models::Pagination::new(listing.posts_index.len(), self.config.url.per_page_size);
+        let page_format = format!("{}{}", listing.prefix, self.config.url.post_page_format);
+        let current_page = pagination.build_each_page(1, &page_format);
 
         // build template vars
-        let mut template_vars = self.template_vars.get_global();
+        let mut template_vars = self.template_vars.get_global_for(listing.language);
         template_vars.pagination = Some(current_page.build_template_vars());
-        let posts = &self.posts[current_page.start..current_page.end];
+        let posts_index = &listing.posts_index[current_page.start..current_page.end];
         let mut posts_vars = Vec::new();
-        for p in posts {
-            let post_vars = self.template_vars.build_postvars(p);
+        for index in posts_index {
+            let post_vars = self.template_vars.build_postvars(&self.posts[*index]);
             posts_vars.push(post_vars);
         }
         template_vars.posts = Some(posts_vars);
+        // index of each language links to others
+        if !self.languages.is_empty() {
+            template_vars.alternates = self
+                .listings()
+                .iter()
+                .map(|l| {
+                    let site = self.template_vars.get_global_for(l.language).site;
+                    self.template_vars
+                        .build_alternate(l.language, &site.title, &site.home_url)
+                })
+                .collect();
+        }
 
-        let output_file = self.config.build_dist_html_filepath("index.html", true);
+        let index_url = format!("{}/index.html", listing.prefix);
+        let output_file = self.config.build_dist_html_filepath(&index_url, true);
         // set outputs
-        let dt = Utc
-            .from_local_datetime(&posts[0].datetime.unwrap())
-            .unwrap();
+        let dt = self.build_lastmod(posts_index);
         let outputs = vec![models::Output {
-            visit_url: self.config.build_root_url("index.html"),
+            visit_url: self.config.build_root_url(&index_url),
             output_files: vec![output_file],
             template_vars,
             template_file: self.config.theme.index_template.clone(),
@@ -258,9 +601,7 @@ impl Site<'_> {
     fn build_404_page(&self) -> Result<Vec<models::Output>, Box<dyn std::error::Error>> {
         let template_vars = self.template_vars.get_global();
         let output_file = self.config.build_dist_html_filepath("404", true);
-        let dt = Utc
-            .from_local_datetime(&self.posts[0].datetime.unwrap())
-            .unwrap();
+        let dt = self.build_site_lastmod();
         let outputs = vec![models::Output {
             visit_url: self.config.build_root_url("404"),
             output_files: vec![output_file],
@@ -273,15 +614,22 @@ impl Site<'_> {
         Ok(outputs)
     }
 
-    fn build_archives(&self) -> Result<Vec<models::Output>, Box<dyn std::error::Error>> {
+    fn build_archives(
+        &self,
+        listing: &Listing,
+    ) -> Result<Vec<models::Output>, Box<dyn std::error::Error>> {
+        let listed: std::collections::HashSet<&usize> = listing.posts_index.iter().collect();
         let archives = models::Archive::parse(&self.posts);
         let mut archive_vars = vec![];
         for archive in &archives {
             let mut posts_vars = vec![];
-            for idx in &archive.posts_index {
+            for idx in archive.posts_index.iter().filter(|i| listed.contains(i)) {
                 let post_vars = self.template_vars.build_postvars(&self.posts[*idx]);
                 posts_vars.push(post_vars);
             }
+            if posts_vars.is_empty() {
+                continue;
+            }
             let archive_var = models::ArchiveVars {
                 year: archive.year.clone(),
                 posts: posts_vars,
@@ -289,15 +637,14 @@ impl Site<'_> {
             archive_vars.push(archive_var);
         }
 
-        let mut template_vars = self.template_vars.get_global();
+        let mut template_vars = self.template_vars.get_global_for(listing.language);
         template_vars.archives = Some(archive_vars);
 
-        let dt = Utc
-            .from_local_datetime(&self.posts[0].datetime.unwrap())
-            .unwrap();
-        let output_file = self.config.build_dist_html_filepath("archives", true);
+        let dt = self.build_lastmod(listing.posts_index);
+        let archives_url = format!("{}/archives", listing.prefix);
+        let output_file = self.config.build_dist_html_filepath(&archives_url, true);
         let outputs = vec![models::Output {
-            visit_url: self.config.build_root_url("archives"),
+            visit_url: self.config.build_root_url(&archives_url),
             output_files: vec![output_file],
             template_vars,
             template_file: "archives.hbs".to_string(),
@@ -308,12 +655,35 @@ impl Site<'_> {
         Ok(outputs)
     }
 
-    fn build_rss(&self) -> Result<Vec<models::Output>, Box<dyn std::error::Error>> {
+    fn build_rss(
+        &self,
+        listing: &Listing,
+    ) -> Result<Vec<models::Output>, Box<dyn std::error::Error>> {
+        let site = self.template_vars.get_global_for(listing.language).site;
+        let feed_url = format!("{}/atom.xml", listing.prefix);
+        let outputs = vec![self.build_feed(
+            &site.title,
+            &site.description,
+            &format!("{}/", listing.prefix),
+            &feed_url,
+            listing.posts_index,
+        )];
+        Ok(outputs)
+    }
+
+    fn build_feed(
+        &self,
+        title: &str,
+        description: &str,
+        link: &str,
+        output_url: &str,
+        posts_index: &[usize],
+    ) -> models::Output {
         use rss::{ChannelBuilder, ItemBuilder};
         // add post items
         let mut items = Vec::new();
-        for post in &self.posts {
-            let dt = Local.from_local_datetime(&post.datetime.unwrap()).unwrap();
+        for post in posts_index.iter().map(|i| &self.posts[*i]) {
+            let dt = self.timezone.localize(&post.datetime.unwrap());
             let full_link = self.config.build_full_url(&post.slug_url);
             let item = ItemBuilder::default()
                 .title(Some(post.meta.title.clone()))
@@ -325,19 +695,16 @@ impl Site<'_> {
         }
         // build channel
         let channel = ChannelBuilder::default()
-            .title(self.config.site.title.clone())
-            .link(self.config.build_full_url(""))
+            .title(title.to_string())
+            .link(self.config.build_full_url(link))
             .items(items)
-            .description(self.config.site.description.clone())
+            .description(description.to_string())
             .build();
 
-        // set outputs
-        let output_url = "atom.xml";
+        // set output
         let output_file = self.config.build_dist_filepath(output_url, true);
-        let dt = Utc
-            .from_local_datetime(&self.posts[0].datetime.unwrap())
-            .unwrap();
-        let outputs = vec![models::Output {
+        let dt = self.build_lastmod(posts_index);
+        models::Output {
             visit_url: self.config.build_root_url(output_url),
             output_files: vec![output_file],
             template_vars: self.template_vars.get_global(),
@@ -345,6 +712,24 @@ impl Site<'_> {
             file_content: channel.to_string(),
             lastmod: dt,
             sitemap_priority: 0.8,
+        }
+    }
+
+    fn build_highlight_css(&self) -> Result<Vec<models::Output>, Box<dyn std::error::Error>> {
+        let highlighter = match self.markdown.get_highlighter() {
+            Some(highlighter) if !self.config.highlight.is_inline() => highlighter,
+            _ => return Ok(vec![]),
+        };
+        let css_file = &self.config.highlight.css_file;
+        let output_file = self.config.build_dist_filepath(css_file, true);
+        let outputs = vec![models::Output {
+            visit_url: self.config.build_root_url(css_file),
+            output_files: vec![output_file],
+            template_vars: self.template_vars.get_global(),
+            template_file: "".to_string(),
+            file_content: highlighter.css()?,
+            lastmod: Utc::now(),
+            sitemap_priority: 0.0,
         }];
         Ok(outputs)
     }
@@ -355,10 +740,10 @@ impl Site<'_> {
         // build each page
         for p in &self.pages {
             let output_file = self.config.build_dist_html_filepath(&p.slug_url, true);
-            let mut template_vars = self.template_vars.get_global();
-            template_vars.page = Some(self.template_vars.build_postvars(p));
-            template_vars.title = p.meta.title.clone();
-            let dt = Utc.from_local_datetime(&p.datetime.unwrap()).unwrap();
+            let page_vars = self.template_vars.build_postvars(p);
+            let mut template_vars = self.build_content_global_vars(p, &p.slug_url, &page_vars);
+            template_vars.page = Some(page_vars);
+            let dt = self.to_utc(&p.datetime.unwrap());
             outputs.push(models::Output {
                 visit_url: self.config.build_root_url(&p.slug_url),
                 output_files: vec![output_file],
@@ -399,9 +784,7 @@ impl Site<'_> {
             urls.push(entry);
         }
         let sitemap_outputfile = self.config.build_dist_filepath("sitemap.xml", true);
-        let dt = Utc
-            .from_local_datetime(&self.posts[0].datetime.unwrap())
-            .unwrap();
+        let dt = self.build_site_lastmod();
         let sitemap_output = models::Output {
             visit_url: self.config.build_root_url("sitemap.xml"),
             output_files: vec![sitemap_outputfile],
@@ -447,6 +830,33 @@ impl Site<'_> {
         }
     }
 
+    fn copy_bundle_assets(&self) -> Result<(), Box<dyn std::error::Error>> {
+        for p in self.posts.iter().chain(self.pages.iter()) {
+            let bundle_dir = match &p.bundle_dir {
+                Some(dir) => dir,
+                None => continue,
+            };
+            // resources are next to the index.html of permalink
+            let output_file = self.config.build_dist_html_filepath(&p.slug_url, true);
+            let output_dir = std::path::Path::new(&output_file).parent().unwrap();
+            std::fs::create_dir_all(output_dir)?;
+            for entry in std::fs::read_dir(bundle_dir)? {
+                let entry = entry?;
+                if entry.file_name() == "index.md" {
+                    continue;
+                }
+                let dst = output_dir.join(entry.file_name());
+                if entry.file_type()?.is_dir() {
+                    copy_dir_all(entry.path(), dst)?;
+                } else {
+                    std::fs::copy(entry.path(), dst)?;
+                }
+            }
+            debug!("Copied bundle {} to {}", bundle_dir, output_dir.display());
+        }
+        Ok(())
+    }
+
     pub fn archive(&self) -> Result<String, Box<dyn std::error::Error>> {
         use flate2::write::GzEncoder;
         use flate2::Compression;
@@ -466,16 +876,33 @@ impl Site<'_> {
     }
 }
 
-pub fn markdown_to_html(content: &str) -> String {
-    use pulldown_cmark as cmark;
-    let mut buf = String::new();
-    let options = cmark::Options::ENABLE_FOOTNOTES
-        | cmark::Options::ENABLE_TABLES
-        | cmark::Options::ENABLE_STRIKETHROUGH
-        | cmark::Options::ENABLE_TASKLISTS;
-    let parser = cmark::Parser::new_ext(content, options);
-    cmark::html::push_html(&mut buf, parser);
-    buf
+// relative links in page bundle are resolved from directory of its permalink
+fn build_render_options<'a>(
+    config: &models::Config,
+    p: &models::Post,
+    content_links: &'a models::ContentLinks,
+    theme: &'a models::Theme<'a>,
+) -> models::RenderOptions<'a> {
+    let link_base = p.bundle_dir.as_ref().map(|_| {
+        let url = config.build_root_url(&p.slug_url);
+        if url.ends_with(".html") {
+            url[..url.rfind('/').unwrap() + 1].to_string()
+        } else {
+            format!("{}/", url.trim_end_matches('/'))
+        }
+    });
+    models::RenderOptions {
+        link_base,
+        content_links: Some(content_links),
+        theme: Some(theme),
+        math: p.meta.math.unwrap_or(config.math.enabled),
+        markdown: match &p.meta.markdown {
+            Some(overrides) => config.markdown.with_overrides(overrides),
+            None => config.markdown.clone(),
+        },
+        source_file: p.local_file.clone(),
+        line_offset: p.content_line_offset,
+    }
 }
 
 pub fn copy_dir_all(
@@ -494,3 +921,73 @@ pub fn copy_dir_all(
     }
     Ok(())
 }
+
+#[cfg(test)]
+mod site_options_tests {
+    use super::*;
+
+    #[test]
+    fn test_drafts_visible() {
+        let post = models::Post {
+            meta: models::PostMetadata {
+                draft: Some(true),
+                ..models::PostMetadata::default()
+            },
+            ..Default::default()
+        };
+        let options = SiteOptions {
+            drafts: true,
+            ..SiteOptions::default()
+        };
+        let timezone = models::Timezone::default();
+        assert!(!SiteOptions::default().is_visible(&post, &timezone));
+        assert!(options.is_visible(&post, &timezone));
+        assert!(SiteOptions::default().is_visible(&models::Post::default(), &timezone));
+    }
+
+    #[test]
+    fn test_build_without_visible_posts() {
+        let dir = "test_site_future";
+        let mut config = models::Config::default();
+        config.directory.source = format!("{}/source", dir);
+        config.directory.output = format!("{}/dist", dir);
+        config.directory.themes = format!("{}/themes", dir);
+        config.mkdir_all().unwrap();
+        models::ThemeEmbedAssets::extract(&config.directory.themes).unwrap();
+        let config_file = format!("{}/config.toml", dir);
+        config.to_file(&config_file).unwrap();
+        std::fs::write(
+            config.build_post_uri("future.md"),
+            "---\ntitle: Future\nslug: future\ndate: 2999-01-01\n---\ncontent",
+        )
+        .unwrap();
+
+        // every post is future-dated, site is built without posts
+        let site = Site::load(&config_file, SiteOptions::default()).unwrap();
+        assert!(site.posts.is_empty());
+        let result = site.build();
+        let has_sitemap = std::path::Path::new(dir).join("dist/sitemap.xml").exists();
+        std::fs::remove_dir_all(dir).unwrap();
+        result.unwrap();
+        assert!(has_sitemap);
+    }
+
+    #[test]
+    fn test_future_and_expired_visible() {
+        let now = models::post::parse_time("2022-05-25").unwrap();
+        let mut post = models::Post {
+            datetime: Some(models::post::parse_time("2022-06-01").unwrap()),
+            ..Default::default()
+        };
+        let options = SiteOptions {
+            future: true,
+            ..SiteOptions::default()
+        };
+        assert!(!SiteOptions::default().is_visible_at(&post, &now));
+        assert!(options.is_visible_at(&post, &now));
+
+        post.datetime = Some(models::post::parse_time("2022-05-01").unwrap());
+        post.expires_datetime = Some(models::post::parse_time("2022-05-20").unwrap());
+        assert!(!options.is_visible_at(&post, &now));
+    }
+}
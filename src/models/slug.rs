@@ -0,0 +1,110 @@
+use crate::models;
+
+// build slug from text, words are joined by separator, other chars are dropped
+pub fn slugify(text: &str, config: &models::SlugConfig) -> String {
+    let text = if config.transliterate {
+        deunicode::deunicode(text)
+    } else {
+        text.to_string()
+    };
+    let mut slug = String::new();
+    let mut pending_separator = false;
+    for c in text.chars() {
+        // apostrophes do not split words, like rust's
+        if c == '\'' || c == '\u{2019}' {
+            continue;
+        }
+        if !c.is_alphanumeric() {
+            pending_separator = !slug.is_empty();
+            continue;
+        }
+        if pending_separator {
+            slug.push_str(&config.separator);
+            pending_separator = false;
+        }
+        match config.case.as_str() {
+            "upper" => slug.extend(c.to_uppercase()),
+            "keep" => slug.push(c),
+            _ => slug.extend(c.to_lowercase()),
+        }
+    }
+    slug
+}
+
+// name of source file without extension, or name of bundle directory
+pub fn file_name(p: &models::Post) -> String {
+    let name = match &p.bundle_dir {
+        Some(dir) => std::path::Path::new(dir).file_name(),
+        None => std::path::Path::new(&p.local_file).file_stem(),
+    };
+    name.map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default()
+}
+
+// slug of post or page without slug, from title or file name in config,
+// the other one is used if it is empty
+pub fn build_slug(p: &models::Post, config: &models::SlugConfig) -> String {
+    let filename = file_name(p);
+    let (first, second) = if config.source == "filename" {
+        (filename.as_str(), p.meta.title.as_str())
+    } else {
+        (p.meta.title.as_str(), filename.as_str())
+    };
+    let slug = slugify(first, config);
+    if slug.is_empty() {
+        return slugify(second, config);
+    }
+    slug
+}
+
+#[cfg(test)]
+mod slug_tests {
+    use super::*;
+
+    #[test]
+    fn test_slugify() {
+        let mut config = models::SlugConfig::default();
+        assert_eq!(slugify("Hello, World!", &config), "hello-world");
+        assert_eq!(
+            slugify("  Rust's  new  Release 1.0 ", &config),
+            "rusts-new-release-1-0"
+        );
+        assert_eq!(slugify("你好世界", &config), "ni-hao-shi-jie");
+        assert_eq!(slugify("Crème brûlée", &config), "creme-brulee");
+        assert_eq!(slugify("!!!", &config), "");
+
+        config.separator = "_".to_string();
+        config.case = "keep".to_string();
+        assert_eq!(slugify("Hello World", &config), "Hello_World");
+        config.case = "upper".to_string();
+        assert_eq!(slugify("Hello World", &config), "HELLO_WORLD");
+
+        config.case = "lower".to_string();
+        config.transliterate = false;
+        assert_eq!(slugify("Über 你好", &config), "über_你好");
+    }
+
+    #[test]
+    fn test_build_slug() {
+        let mut config = models::SlugConfig::default();
+        let mut post = models::Post {
+            meta: models::PostMetadata {
+                title: "First Post".to_string(),
+                ..models::PostMetadata::default()
+            },
+            local_file: "source/posts/2021-first.md".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(build_slug(&post, &config), "first-post");
+        config.source = "filename".to_string();
+        assert_eq!(build_slug(&post, &config), "2021-first");
+        post.bundle_dir = Some("source/posts/my-bundle".to_string());
+        post.local_file = "source/posts/my-bundle/index.md".to_string();
+        assert_eq!(build_slug(&post, &config), "my-bundle");
+
+        // empty title falls back to file name
+        config.source = "title".to_string();
+        post.meta.title = "???".to_string();
+        assert_eq!(build_slug(&post, &config), "my-bundle");
+    }
+}
@@ -7,27 +7,45 @@ pub struct Tag {
     pub posts_index: Vec<usize>,
 }
 
+// :term is used by user-defined taxonomies
 fn build_tag_link(tag: &str, format: &str) -> String {
     let link = format.to_string();
-    link.replace(":tag", tag)
+    link.replace(":tag", tag).replace(":term", tag)
 }
 
 impl Tag {
     pub fn parse(posts: &[models::Post], url_config: &models::UrlConfig) -> Vec<Tag> {
+        Tag::parse_by(
+            posts,
+            |p| p.meta.tags.clone().unwrap_or_default(),
+            &url_config.tag_link_format,
+            &url_config.tag_page_format,
+        )
+    }
+
+    pub fn parse_by<F>(
+        posts: &[models::Post],
+        get_terms: F,
+        link_format: &str,
+        page_format: &str,
+    ) -> Vec<Tag>
+    where
+        F: Fn(&models::Post) -> Vec<String>,
+    {
         let mut tags = std::collections::HashMap::new();
         for (index, p) in posts.iter().enumerate() {
-            for t in p.meta.tags.as_ref().unwrap() {
+            for t in get_terms(p) {
                 let tag = tags.entry(t.clone()).or_insert(Tag {
                     name: t.clone(),
-                    url: build_tag_link(t.as_str(), url_config.tag_link_format.as_str()),
-                    page_format: build_tag_link(t.as_str(), url_config.tag_page_format.as_str()),
+                    url: build_tag_link(t.as_str(), link_format),
+                    page_format: build_tag_link(t.as_str(), page_format),
                     posts_index: vec![],
                 });
                 tag.posts_index.push(index);
             }
         }
         let mut values: Vec<Tag> = tags.into_values().collect();
-        values.sort_by(|a, b| b.posts_index.len().cmp(&a.posts_index.len()));
+        values.sort_by_key(|t| std::cmp::Reverse(t.posts_index.len()));
         values
     }
 }
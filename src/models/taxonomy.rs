@@ -0,0 +1,96 @@
+use crate::models;
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct TaxonomyConfig {
+    pub name: String,
+    pub key: String,
+    pub link_format: String,
+    pub page_format: String,
+    #[serde(default = "default_taxonomy_template")]
+    pub template: String,
+    #[serde(default)]
+    pub feed: bool,
+}
+
+fn default_taxonomy_template() -> String {
+    String::from("posts.hbs")
+}
+
+#[derive(Debug)]
+pub struct Taxonomy {
+    pub name: String,
+    pub key: String,
+    pub template: String,
+    pub feed: bool,
+    pub terms: Vec<models::Tag>,
+}
+
+impl Taxonomy {
+    pub fn parse(posts: &[models::Post], configs: &[TaxonomyConfig]) -> Vec<Taxonomy> {
+        configs
+            .iter()
+            .map(|cfg| Taxonomy {
+                name: cfg.name.clone(),
+                key: cfg.key.clone(),
+                template: cfg.template.clone(),
+                feed: cfg.feed,
+                terms: models::Tag::parse_by(
+                    posts,
+                    |p| p.get_terms(&cfg.key),
+                    &cfg.link_format,
+                    &cfg.page_format,
+                ),
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod taxonomy_tests {
+    use super::*;
+
+    #[test]
+    fn test_taxonomies() {
+        let content = "title: Post one\ndate: 2022-05-25\nslug: one\nseries: rust-101\naudience:\n- beginner\n- developer\n---\ncontent";
+        let post_one = models::Post::from_str(content).unwrap();
+        let content =
+            "title: Post two\ndate: 2022-05-26\nslug: two\nseries: [rust-101]\n---\ncontent";
+        let post_two = models::Post::from_str(content).unwrap();
+        let posts = vec![post_one, post_two];
+
+        let configs = vec![
+            TaxonomyConfig {
+                name: "series".to_string(),
+                key: "series".to_string(),
+                link_format: "/series/:term".to_string(),
+                page_format: "/series/:term/page/:page".to_string(),
+                template: default_taxonomy_template(),
+                feed: true,
+            },
+            TaxonomyConfig {
+                name: "audience".to_string(),
+                key: "audience".to_string(),
+                link_format: "/for/:term".to_string(),
+                page_format: "/for/:term/page/:page".to_string(),
+                template: "audience.hbs".to_string(),
+                feed: false,
+            },
+        ];
+        let taxonomies = Taxonomy::parse(&posts, &configs);
+        assert_eq!(taxonomies.len(), 2);
+        assert_eq!(taxonomies[0].terms.len(), 1);
+        assert_eq!(taxonomies[0].terms[0].name, "rust-101");
+        assert_eq!(taxonomies[0].terms[0].url, "/series/rust-101");
+        assert_eq!(taxonomies[0].terms[0].posts_index, vec![0, 1]);
+
+        assert_eq!(taxonomies[1].template, "audience.hbs");
+        assert_eq!(taxonomies[1].terms.len(), 2);
+        let beginner = taxonomies[1]
+            .terms
+            .iter()
+            .find(|t| t.name == "beginner")
+            .unwrap();
+        assert_eq!(beginner.posts_index, vec![0]);
+        assert_eq!(beginner.page_format, "/for/beginner/page/:page");
+    }
+}
@@ -1,6 +1,6 @@
 use crate::models;
+use crate::utils;
 use chrono::NaiveDateTime;
-use handlebars::handlebars_helper;
 use log::debug;
 use std::path::Path;
 
@@ -22,8 +22,33 @@ impl ThemeEmbedAssets {
     }
 }
 
-// define date helper
-handlebars_helper!(date_format: |dt: NaiveDateTime,{fmt:str = "%Y-%m-%d"} | dt.format(fmt).to_string());
+// {{date_format dt fmt="%Y-%m-%d"}}, dt is wall time in site timezone, so %z is its offset
+struct DateFormat {
+    timezone: models::Timezone,
+}
+
+impl handlebars::HelperDef for DateFormat {
+    fn call_inner<'reg: 'rc, 'rc>(
+        &self,
+        h: &handlebars::Helper<'reg, 'rc>,
+        _: &'reg handlebars::Handlebars<'reg>,
+        _: &'rc handlebars::Context,
+        _: &mut handlebars::RenderContext<'reg, 'rc>,
+    ) -> Result<handlebars::ScopedJson<'reg, 'rc>, handlebars::RenderError> {
+        let dt: NaiveDateTime = h
+            .param(0)
+            .and_then(|v| serde_json::from_value(v.value().clone()).ok())
+            .ok_or_else(|| handlebars::RenderError::new("date_format needs a datetime"))?;
+        let fmt = h
+            .hash_get("fmt")
+            .and_then(|v| v.value().as_str())
+            .unwrap_or("%Y-%m-%d");
+        let text = self.timezone.localize(&dt).format(fmt).to_string();
+        Ok(handlebars::ScopedJson::Derived(serde_json::Value::String(
+            text,
+        )))
+    }
+}
 
 pub struct Theme<'a> {
     pub dir: String,
@@ -32,9 +57,22 @@ pub struct Theme<'a> {
 }
 
 impl Theme<'_> {
-    pub fn parse(dir: &str) -> Result<Theme<'static>, Box<dyn std::error::Error>> {
+    pub fn parse(
+        dir: &str,
+        config: &models::Config,
+    ) -> Result<Theme<'static>, Box<dyn std::error::Error>> {
         let mut reg = handlebars::Handlebars::new();
-        reg.register_helper("date_format", Box::new(date_format));
+        reg.register_helper(
+            "date_format",
+            Box::new(DateFormat {
+                timezone: config.get_timezone()?,
+            }),
+        );
+        // strings of site override the ones of theme
+        let mut i18n = models::I18n::new(&config.site.language);
+        i18n.load_dir(&utils::merge_url(dir, "i18n"))?;
+        i18n.load_dir(&config.directory.i18n)?;
+        reg.register_helper("t", Box::new(i18n));
         for entry in walkdir::WalkDir::new(dir) {
             let entry = entry.unwrap();
             let template_file_path = entry.path();
@@ -58,6 +96,22 @@ impl Theme<'_> {
             minify: true,
         })
     }
+
+    // render template shortcodes/<name>.hbs or .html with arguments of shortcode
+    pub fn render_shortcode(
+        &self,
+        name: &str,
+        vars: &serde_json::Value,
+    ) -> Result<String, Box<dyn std::error::Error>> {
+        for ext in ["hbs", "html"] {
+            let template_name = format!("shortcodes/{}.{}", name, ext);
+            if self.reg.has_template(&template_name) {
+                return Ok(self.reg.render(&template_name, vars)?);
+            }
+        }
+        Err(format!("shortcode template not found: {}", name).into())
+    }
+
     pub fn render(
         &self,
         name: &str,
@@ -78,3 +132,24 @@ impl Theme<'_> {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod theme_tests {
+    use super::*;
+
+    #[test]
+    fn test_date_format_helper() {
+        let mut reg = handlebars::Handlebars::new();
+        reg.register_helper(
+            "date_format",
+            Box::new(DateFormat {
+                timezone: models::Timezone::parse("Asia/Shanghai").unwrap(),
+            }),
+        );
+        let data = serde_json::json!({"dt": "2021-03-04T10:05:00"});
+        let render = |tpl: &str| reg.render_template(tpl, &data).unwrap();
+        assert_eq!(render("{{date_format dt}}"), "2021-03-04");
+        assert_eq!(render("{{date_format dt fmt=\"%H:%M %z\"}}"), "10:05 +0800");
+        assert!(reg.render_template("{{date_format \"x\"}}", &data).is_err());
+    }
+}
@@ -0,0 +1,122 @@
+use chrono::{DateTime, FixedOffset, NaiveDateTime, TimeZone};
+
+// timezone of site, times without offset in front matter are wall times in it
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum Timezone {
+    // timezone of build machine, used if it is not set
+    #[default]
+    Local,
+    Named(chrono_tz::Tz),
+    Fixed(FixedOffset),
+}
+
+// parse offset like +08:00, +0800 or -05
+pub fn parse_offset(text: &str) -> Option<FixedOffset> {
+    let sign = match text.as_bytes().first()? {
+        b'+' => 1,
+        b'-' => -1,
+        _ => return None,
+    };
+    let digits = text[1..].replacen(':', "", 1);
+    if !matches!(digits.len(), 2 | 4) || !digits.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let hours: i32 = digits[..2].parse().ok()?;
+    let minutes: i32 = match &digits[2..] {
+        "" => 0,
+        m => m.parse().ok()?,
+    };
+    FixedOffset::east_opt(sign * (hours * 3600 + minutes * 60))
+}
+
+impl Timezone {
+    // parse IANA name like Asia/Shanghai, UTC, or offset like +08:00
+    pub fn parse(name: &str) -> Result<Timezone, String> {
+        let name = name.trim();
+        if let Some(offset) = parse_offset(name) {
+            return Ok(Timezone::Fixed(offset));
+        }
+        name.parse::<chrono_tz::Tz>()
+            .map(Timezone::Named)
+            .map_err(|_| format!("timezone is not valid: {}", name))
+    }
+
+    // datetime of wall time in the timezone, the earlier one if it is ambiguous
+    pub fn localize(&self, naive: &NaiveDateTime) -> DateTime<FixedOffset> {
+        fn resolve<Tz: TimeZone>(tz: &Tz, naive: &NaiveDateTime) -> DateTime<FixedOffset> {
+            // wall time skipped by daylight saving is taken as utc time
+            tz.from_local_datetime(naive)
+                .earliest()
+                .unwrap_or_else(|| tz.from_utc_datetime(naive))
+                .fixed_offset()
+        }
+        match self {
+            Timezone::Local => resolve(&chrono::Local, naive),
+            Timezone::Named(tz) => resolve(tz, naive),
+            Timezone::Fixed(offset) => resolve(offset, naive),
+        }
+    }
+
+    // wall time of datetime in the timezone
+    pub fn wall_time(&self, dt: &DateTime<FixedOffset>) -> NaiveDateTime {
+        match self {
+            Timezone::Local => dt.with_timezone(&chrono::Local).naive_local(),
+            Timezone::Named(tz) => dt.with_timezone(tz).naive_local(),
+            Timezone::Fixed(offset) => dt.with_timezone(offset).naive_local(),
+        }
+    }
+
+    pub fn now(&self) -> NaiveDateTime {
+        self.wall_time(&chrono::Utc::now().fixed_offset())
+    }
+}
+
+#[cfg(test)]
+mod timezone_tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_offset() {
+        assert_eq!(parse_offset("+08:00"), FixedOffset::east_opt(8 * 3600));
+        assert_eq!(
+            parse_offset("-0530"),
+            FixedOffset::east_opt(-(5 * 3600 + 30 * 60))
+        );
+        assert_eq!(parse_offset("+09"), FixedOffset::east_opt(9 * 3600));
+        assert_eq!(parse_offset("-03-04"), None);
+        assert_eq!(parse_offset("08:00"), None);
+    }
+
+    #[test]
+    fn test_timezone() {
+        let naive = crate::models::post::parse_time("2021-03-04 10:00:00").unwrap();
+        let tz = Timezone::parse("Asia/Shanghai").unwrap();
+        assert_eq!(
+            tz.localize(&naive).to_rfc3339(),
+            "2021-03-04T10:00:00+08:00"
+        );
+        let utc = Timezone::parse("UTC").unwrap();
+        assert_eq!(
+            utc.wall_time(&tz.localize(&naive)).to_string(),
+            "2021-03-04 02:00:00"
+        );
+        let fixed = Timezone::parse("-05:00").unwrap();
+        assert_eq!(
+            fixed.localize(&naive).to_rfc3339(),
+            "2021-03-04T10:00:00-05:00"
+        );
+        assert!(Timezone::parse("Mars/Olympus").is_err());
+
+        // daylight saving
+        let tz = Timezone::parse("America/New_York").unwrap();
+        let summer = crate::models::post::parse_time("2021-07-01 12:00:00").unwrap();
+        assert_eq!(
+            tz.localize(&summer).to_rfc3339(),
+            "2021-07-01T12:00:00-04:00"
+        );
+        assert_eq!(
+            tz.localize(&naive).to_rfc3339(),
+            "2021-03-04T10:00:00-05:00"
+        );
+    }
+}
@@ -1,4 +1,5 @@
 use crate::models;
+use crate::utils;
 
 #[derive(Debug, Default, Clone, serde::Serialize, serde::Deserialize)]
 pub struct AuthorVars {
@@ -46,6 +47,11 @@ pub struct SiteVars {
     pub author: String,
     pub root_url: String,
     pub full_url: String,
+    // home and feed of current language, root ones for default language
+    pub home_url: String,
+    pub feed_url: String,
+    pub highlight: bool,
+    pub highlight_css: String,
 }
 
 #[derive(Debug, Default, Clone, serde::Serialize, serde::Deserialize)]
@@ -55,6 +61,74 @@ pub struct TagVars {
     pub posts_count: usize,
 }
 
+impl TagVars {
+    pub fn new(t: &models::Tag, config: &models::Config) -> TagVars {
+        TagVars {
+            name: t.name.clone(),
+            url: config.build_root_url(&t.url),
+            posts_count: t.posts_index.len(),
+        }
+    }
+}
+
+// same content in another language, for hreflang links
+#[derive(Debug, Default, Clone, serde::Serialize, serde::Deserialize)]
+pub struct AlternateVars {
+    pub language: String,
+    pub title: String,
+    pub permalink: String,
+    pub full_url: String,
+}
+
+#[derive(Debug, Default, Clone, serde::Serialize, serde::Deserialize)]
+pub struct CategoryVars {
+    pub name: String,
+    pub path: String,
+    pub parent: Option<String>,
+    pub depth: usize,
+    pub url: String,
+    pub posts_count: usize,
+}
+
+impl CategoryVars {
+    pub fn new(c: &models::Category, config: &models::Config) -> CategoryVars {
+        CategoryVars {
+            name: c.name.clone(),
+            path: c.path.clone(),
+            parent: c.parent.clone(),
+            depth: c.depth,
+            url: config.build_root_url(&c.url),
+            posts_count: c.posts_index.len(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct PostNavVars {
+    pub title: String,
+    pub permalink: String,
+    pub date: String,
+    pub datetime: chrono::NaiveDateTime,
+}
+
+impl PostNavVars {
+    pub fn new(p: &models::Post) -> PostNavVars {
+        PostNavVars {
+            title: p.meta.title.clone(),
+            permalink: p.slug_url.clone(),
+            date: p.meta.date.clone(),
+            datetime: p.datetime.unwrap(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct TagNavVars {
+    pub tag: String,
+    pub prev: Option<PostNavVars>,
+    pub next: Option<PostNavVars>,
+}
+
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct PostVars {
     pub title: String,
@@ -63,12 +137,25 @@ pub struct PostVars {
     pub updated: String,
     pub brief: String,
     pub content: String,
+    pub toc: Vec<models::TocItem>,
+    pub word_count: usize,
+    pub reading_minutes: usize,
     pub language: String,
     pub comments: bool,
     pub tags: Vec<TagVars>,
+    pub categories: Vec<CategoryVars>,
+    pub taxonomies: std::collections::HashMap<String, Vec<TagVars>>,
     pub datetime: chrono::NaiveDateTime,
     pub updated_datetime: chrono::NaiveDateTime,
     pub author: AuthorVars,
+    pub is_draft: bool,
+    pub prev: Option<PostNavVars>,
+    pub next: Option<PostNavVars>,
+    pub tag_navs: Vec<TagNavVars>,
+    pub related: Vec<PostNavVars>,
+    pub translations: Vec<AlternateVars>,
+    // custom front matter keys
+    pub extra: std::collections::HashMap<String, serde_json::Value>,
 }
 
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
@@ -78,6 +165,8 @@ pub struct PaginationVars {
     pub next: usize,
     pub total: usize,
     pub total_pages: usize,
+    // posts per page
+    pub size: usize,
     pub has_next: bool,
     pub has_prev: bool,
     pub current_url: String,
@@ -112,11 +201,17 @@ pub struct GlobalVars {
     pub navs: Vec<NavVars>,
     pub tags: Option<Vec<TagVars>>,
     pub current_tag: Option<TagVars>,
+    pub categories: Option<Vec<CategoryVars>>,
+    pub current_category: Option<CategoryVars>,
+    pub taxonomies: Option<std::collections::HashMap<String, Vec<TagVars>>>,
+    pub current_taxonomy: Option<String>,
+    pub current_term: Option<TagVars>,
     pub pagination: Option<PaginationVars>,
     pub post: Option<PostVars>,
     pub page: Option<PostVars>,
     pub posts: Option<Vec<PostVars>>,
     pub archives: Option<Vec<ArchiveVars>>,
+    pub alternates: Vec<AlternateVars>,
     pub app: AppVars,
     pub title: String,
 }
@@ -133,6 +228,10 @@ impl GlobalVars {
                 author: site.config.site.author.clone(),
                 root_url: site.config.build_root_url(""),
                 full_url: site.config.build_full_url(""),
+                home_url: site.config.build_root_url(""),
+                feed_url: site.config.build_root_url("atom.xml"),
+                highlight: site.config.highlight.enabled,
+                highlight_css: String::new(),
             },
             author: AuthorVars::default(),
             navs: site
@@ -146,11 +245,17 @@ impl GlobalVars {
                 .collect(),
             current_tag: None,
             tags: None,
+            categories: None,
+            current_category: None,
+            taxonomies: None,
+            current_taxonomy: None,
+            current_term: None,
             pagination: None,
             post: None,
             page: None,
             posts: None,
             archives: None,
+            alternates: vec![],
             app: AppVars {
                 name: "PuGo".to_string(),
                 version: crate_version!().to_string(),
@@ -168,23 +273,110 @@ impl GlobalVars {
                 })
                 .collect(),
         );
+        vars.categories = Some(
+            site.categories
+                .iter()
+                .map(|c| CategoryVars::new(c, &site.config))
+                .collect(),
+        );
+        vars.taxonomies = Some(
+            site.taxonomies
+                .iter()
+                .map(|tx| {
+                    let terms = tx
+                        .terms
+                        .iter()
+                        .map(|t| TagVars::new(t, &site.config))
+                        .collect();
+                    (tx.name.clone(), terms)
+                })
+                .collect(),
+        );
+        if site.config.highlight.enabled && !site.config.highlight.is_inline() {
+            vars.site.highlight_css = site.config.build_root_url(&site.config.highlight.css_file);
+        }
         vars.author = AuthorVars::new(&site.config.get_default_author());
+        if let Some(language_config) = site.config.languages.get(&site.config.site.language) {
+            vars.apply_language_config(language_config);
+        }
         vars
     }
+
+    // global vars of listings and posts in the language
+    pub fn with_language(mut self, site: &models::Site, language: &models::Language) -> GlobalVars {
+        self.site.title = site.config.site.title.clone();
+        self.site.subtitle = site.config.site.subtitle.clone();
+        self.site.description = site.config.site.description.clone();
+        if let Some(language_config) = site.config.languages.get(&language.code) {
+            self.apply_language_config(language_config);
+        }
+        self.site.language = language.code.clone();
+        self.site.home_url = site.config.build_root_url(&format!("{}/", language.prefix));
+        self.site.feed_url = site
+            .config
+            .build_root_url(&format!("{}/atom.xml", language.prefix));
+        self.tags = Some(
+            language
+                .tags
+                .iter()
+                .map(|t| TagVars::new(t, &site.config))
+                .collect(),
+        );
+        self
+    }
+
+    fn apply_language_config(&mut self, language_config: &models::LanguageConfig) {
+        if let Some(title) = &language_config.title {
+            self.site.title = title.clone();
+        }
+        if let Some(subtitle) = &language_config.subtitle {
+            self.site.subtitle = subtitle.clone();
+        }
+        if let Some(description) = &language_config.description {
+            self.site.description = description.clone();
+        }
+    }
 }
 
 #[derive(Debug, Default)]
 pub struct TemplateVars {
     cache_tags: std::collections::HashMap<String, TagVars>,
+    cache_categories: std::collections::HashMap<String, CategoryVars>,
+    cache_terms: std::collections::HashMap<String, std::collections::HashMap<String, TagVars>>,
+    cache_taxonomy_keys: Vec<(String, String)>,
+    cache_post_navs: Vec<PostNavVars>,
     cache_global_vars: Option<GlobalVars>,
+    // global vars and tags of languages with own listings
+    cache_language_globals: std::collections::HashMap<String, GlobalVars>,
+    cache_language_tags:
+        std::collections::HashMap<String, std::collections::HashMap<String, TagVars>>,
+    base_url: String,
 }
 
 impl TemplateVars {
     pub fn new(site: &models::Site) -> TemplateVars {
         let mut vars = TemplateVars {
             cache_tags: std::collections::HashMap::new(),
+            cache_categories: std::collections::HashMap::new(),
+            cache_terms: std::collections::HashMap::new(),
+            cache_taxonomy_keys: vec![],
+            cache_post_navs: site.posts.iter().map(PostNavVars::new).collect(),
             cache_global_vars: Some(GlobalVars::new(site)),
+            cache_language_globals: std::collections::HashMap::new(),
+            cache_language_tags: std::collections::HashMap::new(),
+            base_url: site.config.url.base.clone(),
         };
+        for language in &site.languages {
+            let global = vars.get_global().with_language(site, language);
+            vars.cache_language_globals
+                .insert(language.code.clone(), global);
+            let tags = language
+                .tags
+                .iter()
+                .map(|t| (t.name.clone(), TagVars::new(t, &site.config)))
+                .collect();
+            vars.cache_language_tags.insert(language.code.clone(), tags);
+        }
         for t in &site.tags {
             let tag_vars = TagVars {
                 name: t.name.clone(),
@@ -193,6 +385,18 @@ impl TemplateVars {
             };
             vars.cache_tags.insert(t.name.clone(), tag_vars);
         }
+        for c in &site.categories {
+            vars.cache_categories
+                .insert(c.path.clone(), CategoryVars::new(c, &site.config));
+        }
+        for tx in &site.taxonomies {
+            vars.cache_taxonomy_keys
+                .push((tx.name.clone(), tx.key.clone()));
+            let terms = vars.cache_terms.entry(tx.name.clone()).or_default();
+            for t in &tx.terms {
+                terms.insert(t.name.clone(), TagVars::new(t, &site.config));
+            }
+        }
         vars
     }
 
@@ -200,10 +404,46 @@ impl TemplateVars {
         self.cache_global_vars.as_ref().unwrap().clone()
     }
 
+    // global vars of the language, default ones if it has no own listings
+    pub fn get_global_for(&self, language: &str) -> GlobalVars {
+        match self.cache_language_globals.get(language) {
+            Some(global) => global.clone(),
+            None => self.get_global(),
+        }
+    }
+
     pub fn get_tag(&self, tag: &str) -> Option<TagVars> {
         self.cache_tags.get(tag).cloned()
     }
 
+    pub fn get_language_tag(&self, language: &str, tag: &str) -> Option<TagVars> {
+        match self.cache_language_tags.get(language) {
+            Some(tags) => tags.get(tag).cloned(),
+            None => self.get_tag(tag),
+        }
+    }
+
+    pub fn build_alternate(&self, language: &str, title: &str, url: &str) -> AlternateVars {
+        AlternateVars {
+            language: language.to_string(),
+            title: title.to_string(),
+            permalink: url.to_string(),
+            full_url: utils::merge_url(&self.base_url, url),
+        }
+    }
+
+    pub fn get_category(&self, path: &str) -> Option<CategoryVars> {
+        self.cache_categories.get(path).cloned()
+    }
+
+    pub fn get_term(&self, taxonomy: &str, term: &str) -> Option<TagVars> {
+        self.cache_terms.get(taxonomy)?.get(term).cloned()
+    }
+
+    fn get_post_nav(&self, index: Option<usize>) -> Option<PostNavVars> {
+        self.cache_post_navs.get(index?).cloned()
+    }
+
     pub fn build_postvars(&self, p: &models::Post) -> PostVars {
         let mut post_vars = PostVars {
             title: p.meta.title.clone(),
@@ -213,16 +453,65 @@ impl TemplateVars {
             updated: p.meta.updated.as_ref().unwrap().clone(),
             brief: p.brief_html.clone(),
             content: p.content_html.clone(),
+            toc: p.toc.clone(),
+            word_count: p.word_count,
+            reading_minutes: p.reading_minutes,
             language: p.meta.language.as_ref().unwrap().clone(),
             comments: p.meta.comments.unwrap(),
             tags: vec![],
+            categories: vec![],
+            taxonomies: std::collections::HashMap::new(),
             datetime: p.datetime.unwrap(),
             updated_datetime: p.updated_datetime.unwrap(),
+            is_draft: p.is_draft(),
+            prev: self.get_post_nav(p.neighbours.prev),
+            next: self.get_post_nav(p.neighbours.next),
+            tag_navs: p
+                .tag_neighbours
+                .iter()
+                .map(|(tag, neighbours)| TagNavVars {
+                    tag: tag.clone(),
+                    prev: self.get_post_nav(neighbours.prev),
+                    next: self.get_post_nav(neighbours.next),
+                })
+                .collect(),
+            related: p
+                .related_index
+                .iter()
+                .filter_map(|i| self.get_post_nav(Some(*i)))
+                .collect(),
+            translations: p
+                .translations
+                .iter()
+                .map(|t| self.build_alternate(&t.language, &t.title, &t.url))
+                .collect(),
+            extra: p.meta.extra.clone(),
         };
-        let tags = p.meta.tags.as_ref().unwrap();
-        for t in tags {
-            let tag = self.cache_tags.get(t).unwrap();
-            post_vars.tags.push(tag.clone());
+        let language = p.meta.language.as_deref().unwrap_or_default();
+        for t in p.meta.tags.as_ref().unwrap() {
+            if let Some(tag) = self.get_language_tag(language, t) {
+                post_vars.tags.push(tag);
+            }
+        }
+        // categories from top level to the post's own category
+        if let Some(categories) = &p.meta.categories {
+            for depth in 1..=categories.len() {
+                let path = models::category::build_category_path(&categories[..depth]);
+                if let Some(category) = self.cache_categories.get(&path) {
+                    if !post_vars.categories.iter().any(|c| c.path == path) {
+                        post_vars.categories.push(category.clone());
+                    }
+                }
+            }
+        }
+        // user-defined taxonomies, key of front matter is read from taxonomy config
+        for (name, key) in &self.cache_taxonomy_keys {
+            let terms = p
+                .get_terms(key)
+                .iter()
+                .filter_map(|t| self.get_term(name, t))
+                .collect();
+            post_vars.taxonomies.insert(name.clone(), terms);
         }
         post_vars
     }
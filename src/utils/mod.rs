@@ -1,2 +1,6 @@
 mod url;
 pub use url::merge_url;
+pub use url::url_host;
+
+mod words;
+pub use words::count_words;
@@ -6,3 +6,29 @@ pub fn merge_url(base: &str, url: &str) -> String {
     merged.push_str(url);
     merged
 }
+
+// host of absolute url like https://example.com:8080/a, or scheme relative //example.com/a
+pub fn url_host(url: &str) -> Option<&str> {
+    let rest = match url.split_once("://") {
+        Some((scheme, rest)) if !scheme.is_empty() && !scheme.contains('/') => rest,
+        _ => url.strip_prefix("//")?,
+    };
+    let host = rest.split(['/', '?', '#']).next().unwrap_or(rest);
+    let host = host.rsplit_once('@').map_or(host, |(_, h)| h);
+    let host = host.split(':').next().unwrap_or(host);
+    (!host.is_empty()).then_some(host)
+}
+
+#[cfg(test)]
+mod url_tests {
+    use super::*;
+
+    #[test]
+    fn test_url_host() {
+        assert_eq!(url_host("https://example.com"), Some("example.com"));
+        assert_eq!(url_host("http://localhost:19292/a?b"), Some("localhost"));
+        assert_eq!(url_host("//cdn.example.com/x.js"), Some("cdn.example.com"));
+        assert_eq!(url_host("/posts/a"), None);
+        assert_eq!(url_host("mailto:a@example.com"), None);
+    }
+}
@@ -0,0 +1,76 @@
+#[derive(Debug, Default, PartialEq)]
+pub struct WordCount {
+    pub words: usize,
+    pub cjk_chars: usize,
+}
+
+impl WordCount {
+    pub fn total(&self) -> usize {
+        self.words + self.cjk_chars
+    }
+
+    pub fn reading_minutes(&self, words_per_minute: usize, cjk_chars_per_minute: usize) -> usize {
+        let minutes = self.words as f64 / words_per_minute.max(1) as f64
+            + self.cjk_chars as f64 / cjk_chars_per_minute.max(1) as f64;
+        (minutes.ceil() as usize).max(1)
+    }
+}
+
+// chinese and japanese are written without spaces, so each char is one word
+fn is_cjk(c: char) -> bool {
+    matches!(c as u32,
+        0x3040..=0x30FF // hiragana, katakana
+        | 0x3400..=0x4DBF // cjk extension a
+        | 0x4E00..=0x9FFF // cjk unified ideographs
+        | 0xF900..=0xFAFF // cjk compatibility ideographs
+        | 0x20000..=0x2A6DF // cjk extension b
+    )
+}
+
+pub fn count_words(text: &str) -> WordCount {
+    let mut count = WordCount::default();
+    let mut in_word = false;
+    for c in text.chars() {
+        if is_cjk(c) {
+            count.cjk_chars += 1;
+            in_word = false;
+        } else if c.is_alphanumeric() {
+            if !in_word {
+                count.words += 1;
+                in_word = true;
+            }
+        } else if c.is_whitespace() || !matches!(c, '\'' | '-' | '_' | '.') {
+            in_word = false;
+        }
+    }
+    count
+}
+
+#[cfg(test)]
+mod words_tests {
+    use super::*;
+
+    #[test]
+    fn test_count_words() {
+        let count = count_words("Hello, world! It's a well-known v1.2 example.");
+        assert_eq!(count.words, 7);
+        assert_eq!(count.cjk_chars, 0);
+
+        let count = count_words("使用 PuGo 构建博客，简单快速。");
+        assert_eq!(count.words, 1);
+        assert_eq!(count.cjk_chars, 10);
+        assert_eq!(count.total(), 11);
+
+        assert_eq!(count_words(" -- ** ").total(), 0);
+    }
+
+    #[test]
+    fn test_reading_minutes() {
+        let count = WordCount {
+            words: 450,
+            cjk_chars: 500,
+        };
+        assert_eq!(count.reading_minutes(300, 500), 3);
+        assert_eq!(WordCount::default().reading_minutes(300, 500), 1);
+    }
+}